@@ -1,3 +1,5 @@
+use utils::matcher::MultiMatcher;
+
 #[allow(dead_code)]
 fn parse_line_part_01( line: &str ) -> u32
 {
@@ -25,59 +27,173 @@ fn test_parse_line_part_01()
     assert_eq!( parse_line_part_01( "He5llo11o" ), 51 );
 }
 
-fn forward_search( line: &str, patterns: &[&str] ) -> u32
+struct DigitVocabulary
+{
+    words: Vec< ( String, u32 ) >,
+}
+
+const DIGITS  : &[&str] = &[ "1", "2", "3", "4", "5", "6", "7", "8", "9" ];
+const ENGLISH : &[&str] = &[ "one", "two", "three", "four", "five", "six", "seven", "eight", "nine" ];
+const GERMAN  : &[&str] = &[ "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun" ];
+const CROATIAN: &[&str] = &[ "jedan", "dva", "tri", "četiri", "pet", "šest", "sedam", "osam", "devet" ];
+
+impl DigitVocabulary
 {
-    for ( i, _ ) in line.char_indices()
+    // plain digits are always part of the vocabulary
+    fn new() -> DigitVocabulary
+    {
+        let words = DIGITS.iter().zip( 1 .. ).map( | ( digit, value ) | ( digit.to_string(), value ) ).collect();
+
+        DigitVocabulary { words }
+    }
+
+    fn from_table( table: &[&str] ) -> DigitVocabulary
     {
-        let substr = &line[i..];
-        for ( pat_index, pattern ) in patterns.iter().enumerate()
+        let mut vocabulary = DigitVocabulary::new();
+
+        for ( word, value ) in table.iter().zip( 1 .. )
         {
-            if substr.starts_with( pattern )
-            {
-                return pat_index as u32 / 2 + 1;
-            }
+            vocabulary.add( word, value );
         }
+
+        vocabulary
     }
-    return 0;
-}
 
-fn backward_search( line: &str, patterns: &[&str] ) -> u32
-{
-    for ( i, _ ) in line.char_indices().rev()
+    fn add( &mut self, word: &str, value: u32 )
+    {
+        self.words.push( ( word.to_string(), value ) );
+    }
+
+    fn english () -> DigitVocabulary { DigitVocabulary::from_table( ENGLISH  ) }
+    fn german  () -> DigitVocabulary { DigitVocabulary::from_table( GERMAN   ) }
+    fn croatian() -> DigitVocabulary { DigitVocabulary::from_table( CROATIAN ) }
+
+    fn by_name( name: &str ) -> Option< DigitVocabulary >
+    {
+        match name
+        {
+            "english"  => Some( DigitVocabulary::english () ),
+            "german"   => Some( DigitVocabulary::german  () ),
+            "croatian" => Some( DigitVocabulary::croatian() ),
+            _          => None,
+        }
+    }
+
+    // one "word value" pair per line, empty lines and lines starting with '#' are ignored
+    fn parse( table: &str ) -> Result< DigitVocabulary, String >
     {
-        let substr = &line[ .. ( i + 1 ) ];
-        for ( pat_index, pattern ) in patterns.iter().enumerate()
+        let mut vocabulary = DigitVocabulary::new();
+
+        for ( line_number, line ) in table.lines().enumerate()
         {
-            if substr.ends_with( pattern )
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with( '#' )
+            {
+                continue;
+            }
+
+            let parsed = line.split_once( char::is_whitespace ).and_then
+            (
+                | ( word, value ) | Some( ( word, value.trim().parse::< u32 >().ok()? ) )
+            );
+
+            match parsed
             {
-                return pat_index as u32 / 2 + 1;
+                Some( ( word, value ) ) => vocabulary.add( word, value ),
+                None                    => return Err( format!( "Invalid vocabulary entry at line {}: {}", line_number + 1, line ) ),
             }
         }
+
+        Ok( vocabulary )
     }
-    return 0;
-}
 
+    fn from_file( file_path: &str ) -> Result< DigitVocabulary, String >
+    {
+        let table = std::fs::read_to_string( file_path ).map_err( | e | format!( "Failed to read {}: {}", file_path, e ) )?;
+
+        DigitVocabulary::parse( &table )
+    }
+
+    // built-in vocabulary name or path to a vocabulary file
+    fn select( name_or_path: &str ) -> Result< DigitVocabulary, String >
+    {
+        match DigitVocabulary::by_name( name_or_path )
+        {
+            Some( vocabulary ) => Ok( vocabulary ),
+            None               => DigitVocabulary::from_file( name_or_path ),
+        }
+    }
 
-const PATTERNS : &[&str] = &[ "1", "one", "2", "two", "3", "three", "4", "four", "5", "five", "6", "six", "7", "seven", "8", "eight", "9", "nine" ];
+    fn matcher( &self ) -> DigitMatcher
+    {
+        let words: Vec< &str > = self.words.iter().map( | ( word, _ ) | word.as_str() ).collect();
+
+        DigitMatcher
+        {
+            matcher: MultiMatcher::new( &words ),
+            values : self.words.iter().map( | ( _, value ) | *value ).collect(),
+        }
+    }
+}
 
-fn parse_line_part_02( line: &str, patterns: &[&str] ) -> u32
+// vocabulary compiled into a single automaton, built once and reused for every line
+struct DigitMatcher
 {
-    let first_digit = forward_search ( line, patterns );
-    let last_digit  = backward_search( line, patterns );
+    matcher: MultiMatcher,
+    values : Vec< u32 >,
+}
 
-    first_digit * 10 + last_digit
+fn parse_line_part_02( line: &str, digits: &DigitMatcher ) -> u32
+{
+    match digits.matcher.first_and_last( line )
+    {
+        Some( ( first, last ) ) => digits.values[ first.pattern ] * 10 + digits.values[ last.pattern ],
+        None                    => 0,
+    }
 }
 
 #[test]
 fn test_parse_line_part_02()
 {
-    assert_eq!( parse_line_part_02( "Hello1"   , PATTERNS ), 11 );
-    assert_eq!( parse_line_part_02( "5Hello1"  , PATTERNS ), 51 );
-    assert_eq!( parse_line_part_02( "Hello"    , PATTERNS ), 0  );
-    assert_eq!( parse_line_part_02( "He5llo11o", PATTERNS ), 51 );
+    let english = DigitVocabulary::english().matcher();
+
+    assert_eq!( parse_line_part_02( "Hello1"   , &english ), 11 );
+    assert_eq!( parse_line_part_02( "5Hello1"  , &english ), 51 );
+    assert_eq!( parse_line_part_02( "Hello"    , &english ), 0  );
+    assert_eq!( parse_line_part_02( "He5llo11o", &english ), 51 );
+
+    assert_eq!( parse_line_part_02( "two7eight"  , &english ), 28 );
+    assert_eq!( parse_line_part_02( "Hello1three", &english ), 13 );
+
+    // overlapping words resolve independently from both ends
+    assert_eq!( parse_line_part_02( "eightwo"    , &english ), 82 );
+    assert_eq!( parse_line_part_02( "xoneightx"  , &english ), 18 );
+
+    // non-ASCII input must not panic
+    assert_eq!( parse_line_part_02( "ž3→twoé"    , &english ), 32 );
+}
+
+#[test]
+fn test_other_vocabularies()
+{
+    let german   = DigitVocabulary::german().matcher();
+    let croatian = DigitVocabulary::croatian().matcher();
+
+    assert_eq!( parse_line_part_02( "achtzweinsx", &german ), 81 );
+    assert_eq!( parse_line_part_02( "fünf"       , &german ), 55 );
+    assert_eq!( parse_line_part_02( "twoneight"  , &german ), 0  );
 
-    assert_eq!( parse_line_part_02( "two7eight"  , PATTERNS ), 28 );
-    assert_eq!( parse_line_part_02( "Hello1three", PATTERNS ), 13 );
+    assert_eq!( parse_line_part_02( "četiripetšest", &croatian ), 46 );
+    assert_eq!( parse_line_part_02( "osam3dvatri"  , &croatian ), 83 );
+
+    let custom = DigitVocabulary::parse( "# roman\nI 1\nII 2\n\nIV 4\nV 5\n" ).unwrap().matcher();
+
+    assert_eq!( parse_line_part_02( "xIVx", &custom ), 44 );
+    assert_eq!( parse_line_part_02( "II7V", &custom ), 25 );
+
+    assert!( DigitVocabulary::parse( "one" ).is_err() );
+    assert!( DigitVocabulary::parse( "one x" ).is_err() );
 }
 
 fn main() {
@@ -87,10 +203,13 @@ fn main() {
     println!( "File path is {}", file_path );
     let input = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
+    let vocabulary_name = args.get( 2 ).map( String::as_str ).unwrap_or( "english" );
+    let digits          = DigitVocabulary::select( vocabulary_name ).unwrap().matcher();
+
     let mut sum: u32 = 0;
     for line in input.lines()
     {
-        sum += parse_line_part_02( line, PATTERNS );
+        sum += parse_line_part_02( line, &digits );
     }
 
     println!( "Total sum: {}", sum );
@@ -1,42 +1,66 @@
-#[derive( Debug, PartialEq, Clone )]
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive( Debug, PartialEq, Clone, Default )]
 struct CubeSet
 {
-    red  : u32,
-    green: u32,
-    blue : u32
+    cubes: BTreeMap< String, u32 >,
 }
 
 impl CubeSet {
-    fn max( &mut self, other: CubeSet )
+    fn from_counts( counts: &[ ( &str, u32 ) ] ) -> CubeSet
+    {
+        CubeSet { cubes: counts.iter().map( | ( color, count ) | ( color.to_string(), *count ) ).collect() }
+    }
+
+    fn count( &self, color: &str ) -> u32
+    {
+        self.cubes.get( color ).copied().unwrap_or( 0 )
+    }
+
+    fn max( &mut self, other: &CubeSet )
+    {
+        for ( color, count ) in &other.cubes
+        {
+            let current = self.cubes.entry( color.clone() ).or_insert( 0 );
+            *current = ( *current ).max( *count );
+        }
+    }
+
+    // colors missing from the set count as zero cubes
+    fn power( &self, colors: &BTreeSet< String > ) -> u32
     {
-        self.red   = self.red  .max( other.red   );
-        self.green = self.green.max( other.green );
-        self.blue  = self.blue .max( other.blue  );
+        colors.iter().map( | color | self.count( color ) ).product()
     }
 
-    fn power( &self ) -> u32
+    // colors missing from the bag count as zero cubes, so any unknown color makes the set impossible
+    fn fits_in( &self, bag_contents: &CubeSet ) -> bool
+    {
+        self.cubes.iter().all( | ( color, count ) | *count <= bag_contents.count( color ) )
+    }
+}
+
+impl fmt::Display for CubeSet
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
     {
-        self.red * self.green * self.blue
+        let description: Vec< String > = self.cubes.iter().map( | ( color, count ) | format!( "{} {}", count, color ) ).collect();
+
+        write!( f, "{}", description.join( ", " ) )
     }
 }
 
 fn parse_set( set_description: &str ) -> CubeSet
 {
-    let mut cubes = CubeSet { red: 0, green: 0, blue: 0 };
+    let mut cubes = CubeSet::default();
 
     for desc in set_description.split( ',' )
     {
-        let desc        = desc.trim();
-        let number: u32 = desc.split( ' ' ).next().unwrap().parse().unwrap();
+        let ( number, cube_type ) = desc.trim().split_once( ' ' ).unwrap();
 
-        let cube_type = desc.split(' ').nth( 1 ).unwrap();
+        let number: u32 = number.parse().unwrap();
 
-        match cube_type {
-            "red"   => { cubes.red   += number; },
-            "green" => { cubes.green += number; },
-            "blue"  => { cubes.blue  += number; },
-            &_      => {                        },
-        }
+        *cubes.cubes.entry( cube_type.trim().to_string() ).or_insert( 0 ) += number;
     }
 
     cubes
@@ -45,59 +69,124 @@ fn parse_set( set_description: &str ) -> CubeSet
 #[test]
 fn test_set_parsing()
 {
-    assert_eq!( parse_set( "4 blue, 5 red"   ), CubeSet{ red: 5, green:  0, blue: 4 } );
-    assert_eq!( parse_set( "19 green, 1 red" ), CubeSet{ red: 1, green: 19, blue: 0 } );
+    assert_eq!( parse_set( "4 blue, 5 red"    ), CubeSet::from_counts( &[ ( "red", 5 ), ( "blue" , 4 ) ] ) );
+    assert_eq!( parse_set( "19 green, 1 red"  ), CubeSet::from_counts( &[ ( "red", 1 ), ( "green", 19 ) ] ) );
+    assert_eq!( parse_set( "2 purple, 3 red"  ), CubeSet::from_counts( &[ ( "red", 3 ), ( "purple", 2 ) ] ) );
 }
 
-fn is_game_possible( game: CubeSet, bag_contents: &CubeSet ) -> bool
+struct Game
 {
-    game.red   <= bag_contents.red &&
-    game.green <= bag_contents.green &&
-    game.blue  <= bag_contents.blue
+    id  : u32,
+    sets: Vec< CubeSet >,
 }
 
-#[derive( Debug, Clone )]
-struct Analysis
+impl Game
 {
-    game_id      : u32,
-    game_possible: bool,
-    min_game_set : CubeSet,
+    fn is_possible( &self, bag_contents: &CubeSet ) -> bool
+    {
+        self.sets.iter().all( | set | set.fits_in( bag_contents ) )
+    }
+
+    fn min_game_set( &self ) -> CubeSet
+    {
+        let mut result = CubeSet::default();
+
+        for set in &self.sets
+        {
+            result.max( set );
+        }
+
+        result
+    }
 }
 
-fn analyze_line( line: &str, bag_contents: &CubeSet ) -> Analysis
+fn parse_game( line: &str ) -> Game
 {
     const GAME: &str = "Game ";
 
-    let game_id_begin = &line[ GAME.len() .. ];
-    let game_id_end   = game_id_begin.find( ':' ).unwrap();
+    let ( game_id, game_description ) = line[ GAME.len() .. ].split_once( ':' ).unwrap();
 
-    let mut result = Analysis
+    Game
     {
-        game_id      : game_id_begin[ .. game_id_end ].parse().unwrap(),
-        game_possible: true,
-        min_game_set : CubeSet{ red: 0, green: 0, blue: 0 },
-    };
+        id  : game_id.parse().unwrap(),
+        sets: game_description.split( ';' ).map( parse_set ).collect(),
+    }
+}
 
-    let game_description = &game_id_begin[ ( game_id_end + 1 ) .. ];
+fn default_bag() -> CubeSet
+{
+    CubeSet::from_counts( &[ ( "red", 12 ), ( "green", 13 ), ( "blue", 14 ) ] )
+}
 
-    for game_set in game_description.split( ';' )
+// bag is either given inline ( "12 red, 13 green, 14 blue" ) or as a path to a file with the same contents
+fn parse_bag( bag_argument: &str ) -> CubeSet
+{
+    match std::fs::read_to_string( bag_argument )
     {
-        let cube_set = parse_set( game_set );
+        Ok ( contents ) => parse_set( contents.trim() ),
+        Err( _ )        => parse_set( bag_argument ),
+    }
+}
+
+#[test]
+fn test_game_analysis()
+{
+    let bag = default_bag();
+
+    assert_eq!(  parse_game( "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"        ).id, 2 );
+    assert!   ( !parse_game( "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red" ).is_possible( &bag ) );
+    assert!   ( !parse_game( "Game 5: 1 green, 1 purple"                                                 ).is_possible( &bag ) );
 
-        result.game_possible = result.game_possible && is_game_possible( cube_set.clone(), bag_contents );
-        result.min_game_set.max( cube_set );
+    let bag = parse_bag( "1 green, 1 purple" );
+    assert!   (  parse_game( "Game 5: 1 green, 1 purple"                                                 ).is_possible( &bag ) );
+}
+
+fn possible_games( games: &[ Game ], bag_contents: &CubeSet ) -> Vec< u32 >
+{
+    games.iter().filter( | game | game.is_possible( bag_contents ) ).map( | game | game.id ).collect()
+}
+
+// for every color, the most cubes shown at once in any game and the first game that showed them
+fn color_maxima( games: &[ Game ] ) -> BTreeMap< String, ( u32, u32 ) >
+{
+    let mut maxima: BTreeMap< String, ( u32, u32 ) > = BTreeMap::new();
+
+    for game in games
+    {
+        for ( color, count ) in &game.min_game_set().cubes
+        {
+            let maximum = maxima.entry( color.clone() ).or_insert( ( *count, game.id ) );
+
+            if *count > maximum.0
+            {
+                *maximum = ( *count, game.id );
+            }
+        }
     }
 
-    result
+    maxima
 }
 
-const BAG_CONTENTS: &CubeSet = &CubeSet{ red: 12, green: 13, blue: 14 };
+// smallest bag with which every game is possible
+fn smallest_bag( games: &[ Game ] ) -> CubeSet
+{
+    CubeSet { cubes: color_maxima( games ).into_iter().map( | ( color, ( count, _ ) ) | ( color, count ) ).collect() }
+}
 
 #[test]
-fn test_line_analysis()
+fn test_queries()
 {
-    assert_eq!(  analyze_line( "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"        , BAG_CONTENTS ).game_id, 2    );
-    assert!   ( !analyze_line( "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red", BAG_CONTENTS ).game_possible );
+    let games: Vec< Game > =
+    [
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+    ].iter().map( | line | parse_game( line ) ).collect();
+
+    assert_eq!( possible_games( &games, &default_bag() ), vec![ 1, 2 ] );
+    assert_eq!( possible_games( &games, &smallest_bag( &games ) ), vec![ 1, 2, 3 ] );
+    assert_eq!( smallest_bag( &games ), parse_set( "20 red, 13 green, 6 blue" ) );
+    assert_eq!( color_maxima( &games )[ "blue" ], ( 6, 1 ) );
 }
 
 fn main()
@@ -105,19 +194,36 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
+    let bag_contents = match std::env::args().nth( 2 )
+    {
+        Some( bag_argument ) => parse_bag( &bag_argument ),
+        None                 => default_bag(),
+    };
+
+    let games: Vec< Game > = input.lines().map( parse_game ).collect();
+
+    let colors: BTreeSet< String > = games.iter().flat_map( | game | game.min_game_set().cubes.into_keys() ).collect();
+
     let mut part_01_sum: u32 = 0;
     let mut part_02_sum: u32 = 0;
 
-    for line in input.lines()
+    for game in &games
     {
-        let analysis = analyze_line( line, BAG_CONTENTS );
-
-        if analysis.game_possible
+        if game.is_possible( &bag_contents )
         {
-            part_01_sum += analysis.game_id;
+            part_01_sum += game.id;
         }
 
-        part_02_sum += analysis.min_game_set.power();
+        part_02_sum += game.min_game_set().power( &colors );
+    }
+
+    println!( "Bag: {}", bag_contents );
+    println!( "Possible games: {:?}", possible_games( &games, &bag_contents ) );
+    println!( "Smallest bag for all games: {}", smallest_bag( &games ) );
+
+    for ( color, ( count, game_id ) ) in color_maxima( &games )
+    {
+        println!( "Max {} cubes: {} (game {})", color, count, game_id );
     }
 
     println!( "Total sum (part 01): {}", part_01_sum );
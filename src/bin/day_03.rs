@@ -1,250 +1,395 @@
-fn is_part_number( line_index: usize, lines: &[ &str ], first_digit_index: usize, number_length: usize ) -> bool
-{
-    let sanitized_pos_begin = if first_digit_index > 0 { first_digit_index - 1 } else { first_digit_index };
+use utils::matrix::{from_str_input, Matrix, MatrixTrait};
 
-    let lines_length = lines[ line_index ].len(); // assume all lines are equal length
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+enum Cell
+{
+    Empty,
+    Digit( u8 ),
+    Symbol( char ),
+}
 
-    let sanitized_pos_end = if first_digit_index + number_length >= lines_length { first_digit_index + number_length } else { first_digit_index + number_length + 1 };
+impl Cell
+{
+    fn new( c: u8 ) -> Cell
+    {
+        match c
+        {
+            b'.'         => Cell::Empty,
+            b'0' ..= b'9' => Cell::Digit( c - b'0' ),
+            _            => Cell::Symbol( c as char ),
+        }
+    }
+}
 
-    let not_symbol = | c: char | { c == '.' || c.is_digit( 10 ) };
+// number spanning columns col_begin .. col_end in a single row
+#[derive( Debug, Clone, PartialEq, Eq )]
+struct NumberSpan
+{
+    value    : usize,
+    row      : usize,
+    col_begin: usize,
+    col_end  : usize,
+}
 
-    let no_symbols = | slice: &str | { slice.chars().all( not_symbol ) };
+#[derive( Debug, Clone, PartialEq, Eq )]
+struct SymbolCell
+{
+    symbol: char,
+    row   : usize,
+    col   : usize,
+}
 
-    if first_digit_index > 0 && !not_symbol( lines[ line_index ].chars().nth( first_digit_index - 1 ).unwrap() )
-    {
-        return true;
-    }
+struct Schematic
+{
+    grid   : Matrix< Cell >,
+    numbers: Vec< NumberSpan >,
+    symbols: Vec< SymbolCell >,
 
-    if first_digit_index + number_length < lines_length - 1 && !not_symbol( lines[ line_index ].chars().nth( first_digit_index + number_length  ).unwrap() )
-    {
-        return true;
-    }
+    // adjacency graph between numbers and symbols, as indices into numbers and symbols
+    number_neighbours: Vec< Vec< usize > >,
+    symbol_neighbours: Vec< Vec< usize > >,
+}
 
-    if line_index > 0
+impl Schematic
+{
+    fn parse( input: &str ) -> Schematic
     {
-        // check previous line
-        let slice = &lines[ line_index - 1 ][ sanitized_pos_begin .. sanitized_pos_end ];
+        let bytes = from_str_input( input );
 
-        if !no_symbols( slice )
+        let grid = Matrix
         {
-            return true
-        }
-    }
-
-    if line_index < lines.len() - 1
-    {
-        // check next line
+            data  : bytes.data.iter().map( | c | Cell::new( *c ) ).collect(),
+            width : bytes.width,
+            height: bytes.height,
+        };
 
-        let slice = &lines[ line_index + 1][ sanitized_pos_begin .. sanitized_pos_end ];
+        let mut numbers: Vec< NumberSpan > = Vec::new();
+        let mut symbols: Vec< SymbolCell > = Vec::new();
 
-        if !no_symbols( slice )
+        for row in 0 .. grid.height
         {
-            return true;
-        }
-    }
+            let mut current_number: Option< NumberSpan > = None;
 
-    false
-}
+            for col in 0 .. grid.width
+            {
+                match *grid.at( row, col )
+                {
+                    Cell::Digit( digit ) =>
+                    {
+                        let number = current_number.get_or_insert( NumberSpan { value: 0, row, col_begin: col, col_end: col } );
+
+                        number.value   = number.value * 10 + digit as usize;
+                        number.col_end = col + 1;
+                    },
+                    cell =>
+                    {
+                        numbers.extend( current_number.take() );
+
+                        if let Cell::Symbol( symbol ) = cell
+                        {
+                            symbols.push( SymbolCell { symbol, row, col } );
+                        }
+                    },
+                }
+            }
 
-fn deca_digit    ( c: char ) -> bool {  c.is_digit( 10 ) }
-fn not_deca_digit( c: char ) -> bool { !c.is_digit( 10 ) }
+            // number touching the right edge
+            numbers.extend( current_number.take() );
+        }
 
-fn sum_part_numbers_from_line( line_index: usize, lines: &[ &str ] ) -> usize
-{
-    let line = lines[ line_index ];
+        let mut schematic = Schematic
+        {
+            grid,
+            number_neighbours: vec![ Vec::new(); numbers.len() ],
+            symbol_neighbours: vec![ Vec::new(); symbols.len() ],
+            numbers,
+            symbols,
+        };
 
-    let mut part_numbers_sum: usize = 0;
+        schematic.link();
 
-    let mut search_start_index: usize = 0;
+        schematic
+    }
 
-    loop
+    // builds the adjacency graph between numbers and symbols
+    fn link( &mut self )
     {
-        let number_begin = line[ search_start_index .. ].find( deca_digit );
-
-        match number_begin
+        for ( number_index, number ) in self.numbers.iter().enumerate()
         {
-            None => { break; },
-            Some( x ) =>
-            {
-                let first_digit_index = search_start_index + x;
-                let number_end = line[ first_digit_index .. ].find( not_deca_digit );
+            // bounding box around the number, clamped to the grid
+            let row_begin = number.row.saturating_sub( 1 );
+            let row_end   = ( number.row + 2 ).min( self.grid.height );
+            let col_begin = number.col_begin.saturating_sub( 1 );
+            let col_end   = ( number.col_end + 1 ).min( self.grid.width );
 
-                let number_length = match number_end
+            for row in row_begin .. row_end
+            {
+                for col in col_begin .. col_end
                 {
-                    None => { line[ first_digit_index .. ].len() },
-                    Some( y ) => { y },
-                };
+                    if let Cell::Symbol( _ ) = self.grid.at( row, col )
+                    {
+                        // symbols are collected in row-major order
+                        let symbol_index = self.symbols.binary_search_by_key( &( row, col ), | s | ( s.row, s.col ) ).unwrap();
+
+                        self.number_neighbours[ number_index ].push( symbol_index );
+                        self.symbol_neighbours[ symbol_index ].push( number_index );
+                    }
+                }
+            }
+        }
+    }
 
-                let part_number_candidate: usize = line[ first_digit_index .. ( first_digit_index + number_length ) ].parse().unwrap();
+    fn is_part_number( &self, number_index: usize ) -> bool
+    {
+        !self.number_neighbours[ number_index ].is_empty()
+    }
 
-                if is_part_number( line_index, lines, first_digit_index, number_length )
-                {
-                    part_numbers_sum += part_number_candidate;
-                }
+    fn part_numbers( &self ) -> impl Iterator< Item = &NumberSpan >
+    {
+        self.numbers.iter().enumerate().filter( | ( index, _ ) | self.is_part_number( *index ) ).map( | ( _, number ) | number )
+    }
 
-                if first_digit_index + number_length < line.len() - 1
-                {
-                    search_start_index = first_digit_index + number_length + 1;
-                }
-                else
-                {
-                    break;
-                }
-            }
-        };
+    fn adjacent_numbers( &self, symbol_index: usize ) -> impl Iterator< Item = &NumberSpan >
+    {
+        self.symbol_neighbours[ symbol_index ].iter().map( | number_index | &self.numbers[ *number_index ] )
     }
 
-    part_numbers_sum
+    // numbers without any adjacent symbol, i.e. the ones that are not part numbers
+    fn isolated_numbers( &self ) -> impl Iterator< Item = usize > + '_
+    {
+        ( 0 .. self.numbers.len() ).filter( | index | !self.is_part_number( *index ) )
+    }
 }
 
-enum AdjacentNumbers
+fn dot_escape( text: &str ) -> String
 {
-    Single{ num: usize },
-    Dual{ left: Option< usize >, right: Option< usize > },
+    text.replace( '\\', "\\\\" ).replace( '"', "\\\"" )
 }
 
-fn adjacent_number_in_line( line: &str, gear_position: usize ) -> AdjacentNumbers
+// undirected number <-> symbol graph, isolated numbers are grouped in a separate cluster
+fn export_dot( schematic: &Schematic ) -> String
 {
-    let center = line.chars().nth( gear_position ).unwrap();
+    let mut dot = String::from( "graph schematic {\n" );
 
-    let find_left_begin = | end_pos: usize |
+    let number_node = | index: usize |
     {
-        match line[ .. end_pos ].rfind( not_deca_digit )
-        {
-            None => { 0 },
-            Some( x ) => { x + 1 },
-        }
+        let number = &schematic.numbers[ index ];
+        format!
+        (
+            "    n{} [shape=box, label=\"{}\\nrow {}, cols {}..={}\"];\n",
+            index, number.value, number.row, number.col_begin, number.col_end - 1
+        )
     };
 
-    let find_right_end = | start_pos: usize |
+    dot += "    subgraph cluster_isolated {\n";
+    dot += "    label=\"not part numbers\";\n";
+    for index in schematic.isolated_numbers()
+    {
+        dot += &number_node( index );
+    }
+    dot += "    }\n";
+
+    for index in 0 .. schematic.numbers.len()
     {
-        match line[ start_pos .. ].find( not_deca_digit )
+        if schematic.is_part_number( index )
         {
-            None => { line.len() },
-            Some( x ) => { start_pos + x },
+            dot += &number_node( index );
         }
-    };
+    }
 
-    // digit above or below gear - parse a single number
-    if center.is_digit( 10 )
+    for ( index, symbol ) in schematic.symbols.iter().enumerate()
     {
-        let number_begin = find_left_begin( gear_position );
-        let number_end   = find_right_end ( gear_position );
+        dot += &format!
+        (
+            "    s{} [shape=diamond, label=\"{}\\nrow {}, col {}\"];\n",
+            index, dot_escape( &symbol.symbol.to_string() ), symbol.row, symbol.col
+        );
+    }
 
-        return AdjacentNumbers::Single{ num: line[ number_begin .. number_end ].parse().unwrap() };
+    for ( number_index, neighbours ) in schematic.number_neighbours.iter().enumerate()
+    {
+        for symbol_index in neighbours
+        {
+            dot += &format!( "    n{} -- s{};\n", number_index, symbol_index );
+        }
+    }
+
+    dot += "}\n";
+
+    dot
+}
+
+fn csv_escape( text: &str ) -> String
+{
+    if text.contains( [ ',', '"', '\n' ] )
+    {
+        format!( "\"{}\"", text.replace( '"', "\"\"" ) )
+    }
+    else
+    {
+        text.to_string()
     }
-    else  // may have two numbers (left and right)
+}
+
+// one row per number <-> symbol edge, isolated numbers get a row with empty symbol columns;
+// the number columns are inclusive, like the column ranges in the DOT labels and the console output
+fn export_csv( schematic: &Schematic ) -> String
+{
+    let mut csv = String::from( "number,number_row,number_col_begin,number_col_end_inclusive,symbol,symbol_row,symbol_col\n" );
+
+    for ( number_index, number ) in schematic.numbers.iter().enumerate()
     {
-        let mut left_number : Option< usize > = None;
-        let mut right_number: Option< usize > = None;
+        let number_columns = format!( "{},{},{},{}", number.value, number.row, number.col_begin, number.col_end - 1 );
 
-        // left
-        if gear_position > 0 && deca_digit( line.chars().nth( gear_position - 1 ).unwrap() )
+        if !schematic.is_part_number( number_index )
         {
-            let number_begin = find_left_begin( gear_position - 1 );
-
-            left_number = Some( line[ number_begin .. gear_position ].parse().unwrap() );
+            csv += &format!( "{},,,\n", number_columns );
         }
 
-        // right
-        if gear_position < line.len() - 1 && deca_digit( line.chars().nth( gear_position + 1 ).unwrap() )
+        for symbol_index in &schematic.number_neighbours[ number_index ]
         {
-            let number_end = find_right_end( gear_position + 1 );
+            let symbol = &schematic.symbols[ *symbol_index ];
 
-            right_number = Some( line[ ( gear_position + 1 ) .. number_end ].parse().unwrap() );
+            csv += &format!( "{},{},{},{}\n", number_columns, csv_escape( &symbol.symbol.to_string() ), symbol.row, symbol.col );
         }
-
-        return AdjacentNumbers::Dual { left: left_number, right: right_number };
     }
+
+    csv
+}
+
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+enum Aggregation
+{
+    Product,
+    Sum,
 }
 
-fn get_gear_numbers( line_index: usize, lines: &[&str], gear_position: usize ) -> ( Option< usize >, Option< usize > )
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+struct GearRule
 {
-    let mut numbers: [ Option< usize >; 2 ] = [ None, None ];
+    symbol          : Option< char >, // None matches any symbol
+    adjacent_numbers: usize,
+    aggregation     : Aggregation,
+}
 
-    let mut write_pos = 0;
+impl GearRule
+{
+    fn standard() -> GearRule
+    {
+        GearRule { symbol: Some( '*' ), adjacent_numbers: 2, aggregation: Aggregation::Product }
+    }
 
-    let mut analyze_line = | line_index: usize, write_pos: &mut usize |
+    // "<symbol|any>:<number of adjacent numbers>:<product|sum>", e.g. "*:2:product"
+    fn parse( description: &str ) -> Option< GearRule >
     {
-        let mut write_number = | x: Option< usize > |
+        let mut parts = description.split( ':' );
+
+        let symbol = match parts.next()?
         {
-            match x
-            {
-                None => {},
-                Some( _ ) =>
-                {
-                    numbers[ *write_pos ] = x;
-                    *write_pos += 1;
-                }
-            }
+            "any"  => None,
+            symbol => Some( symbol.parse::< char >().ok()? ),
         };
 
-        match adjacent_number_in_line( lines[ line_index ], gear_position )
+        let adjacent_numbers = parts.next()?.parse().ok()?;
+
+        let aggregation = match parts.next()?
         {
-            AdjacentNumbers::Single { num } => { write_number( Some( num ) ); }
-            AdjacentNumbers::Dual { left, right } =>
-            {
-                write_number( left  );
-                write_number( right );
-            }
+            "product" => Aggregation::Product,
+            "sum"     => Aggregation::Sum,
+            _         => return None,
+        };
+
+        if parts.next().is_some()
+        {
+            return None;
         }
-    };
 
-    // line above
-    if line_index > 0
-    {
-        analyze_line( line_index - 1, &mut write_pos );
-    }
-    if write_pos < 2
-    {
-        analyze_line( line_index, &mut write_pos );
+        Some( GearRule { symbol, adjacent_numbers, aggregation } )
     }
-    if write_pos < 2 && line_index < lines.len() - 1
+
+    fn gear_ratio( &self, schematic: &Schematic, symbol_index: usize ) -> Option< usize >
     {
-        analyze_line( line_index + 1, &mut write_pos );
+        let symbol = &schematic.symbols[ symbol_index ];
+
+        if self.symbol.is_some_and( | s | s != symbol.symbol ) || schematic.symbol_neighbours[ symbol_index ].len() != self.adjacent_numbers
+        {
+            return None;
+        }
+
+        let values = schematic.adjacent_numbers( symbol_index ).map( | number | number.value );
+
+        Some
+        (
+            match self.aggregation
+            {
+                Aggregation::Product => values.product(),
+                Aggregation::Sum     => values.sum(),
+            }
+        )
     }
+}
 
-    ( numbers[ 0 ], numbers[ 1 ] )
+fn sum_part_numbers( schematic: &Schematic ) -> usize
+{
+    schematic.part_numbers().map( | number | number.value ).sum()
 }
 
-fn sum_gear_ratios_from_line( line_index: usize, lines: &[&str] ) -> usize
+fn sum_gear_ratios( schematic: &Schematic, rule: &GearRule ) -> usize
 {
-    let mut gear_sum: usize = 0;
+    ( 0 .. schematic.symbols.len() ).filter_map( | symbol_index | rule.gear_ratio( schematic, symbol_index ) ).sum()
+}
 
-    let line = lines[ line_index ];
+#[cfg(test)]
+const TEST_SCHEMATIC: &str = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..";
 
-    let mut gear_search_index: usize = 0;
+#[test]
+fn test_schematic()
+{
+    let schematic = Schematic::parse( TEST_SCHEMATIC );
 
-    loop
-    {
-        let gear_candidate = line[ gear_search_index .. ].find( '*' );
+    assert_eq!( schematic.numbers.len(), 10 );
+    assert_eq!( schematic.numbers[ 0 ], NumberSpan { value: 467, row: 0, col_begin: 0, col_end: 3 } );
+    assert_eq!( *schematic.grid.at( 1, 3 ), Cell::Symbol( '*' ) );
+    assert_eq!( sum_part_numbers( &schematic ), 4361 );
+    assert_eq!( sum_gear_ratios( &schematic, &GearRule::standard() ), 467835 );
 
-        match gear_candidate
-        {
-            None => { break; },
-            Some( x ) =>
-            {
-                let gear_absolute_index = x + gear_search_index;
+    // numbers touching the right edge and symbols in the last column
+    let schematic = Schematic::parse( "..12\n...#\n5..." );
+    assert_eq!( sum_part_numbers( &schematic ), 12 );
+}
 
-                gear_sum += match get_gear_numbers( line_index, lines, gear_absolute_index )
-                {
-                    ( Some( x ), Some( y ) ) => { x * y },
-                    _ => { 0 },
-                };
+#[test]
+fn test_gear_rules()
+{
+    let schematic = Schematic::parse( TEST_SCHEMATIC );
 
-                if gear_absolute_index < line.len() - 1
-                {
-                    gear_search_index = gear_absolute_index + 1;
-                }
-                else
-                {
-                    break;
-                }
-            }
-        }
-    }
+    let sum_rule = GearRule::parse( "*:2:sum" ).unwrap();
+    assert_eq!( sum_gear_ratios( &schematic, &sum_rule ), 467 + 35 + 755 + 598 );
+
+    let single_any = GearRule::parse( "any:1:product" ).unwrap();
+    assert_eq!( sum_gear_ratios( &schematic, &single_any ), 633 + 617 + 592 + 664 );
 
-    gear_sum
+    assert_eq!( GearRule::parse( "*:2:product" ), Some( GearRule::standard() ) );
+    assert_eq!( GearRule::parse( "**:2:product" ), None );
+    assert_eq!( GearRule::parse( "*:2:average" ), None );
+}
+
+#[test]
+fn test_export()
+{
+    let schematic = Schematic::parse( "12.\n..,\n7.." );
+
+    assert_eq!( schematic.isolated_numbers().collect::< Vec< _ > >(), vec![ 1 ] );
+
+    let csv = export_csv( &schematic );
+    let rows: Vec< _ > = csv.lines().skip( 1 ).collect();
+    assert!( csv.starts_with( "number,number_row,number_col_begin,number_col_end_inclusive," ) );
+    assert_eq!( rows, vec![ "12,0,0,1,\",\",1,2", "7,2,0,0,,," ] );
+
+    let dot = export_dot( &schematic );
+    assert!( dot.contains( "n0 -- s0;" ) );
+    assert!( dot.contains( "n1 [shape=box, label=\"7\\nrow 2, cols 0..=0\"];" ) );
+    assert!( dot.starts_with( "graph schematic {" ) && dot.ends_with( "}\n" ) );
 }
 
 fn main()
@@ -252,15 +397,44 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut part_number_sum: usize = 0;
-    let mut gear_ratio_sum: usize = 0;
-    let lines: Vec< &str > = input.lines().collect();
-    for line_index in 0 .. lines.len()
+    let mut gear_rule = GearRule::standard();
+    let mut dot_path: Option< String > = None;
+    let mut csv_path: Option< String > = None;
+
+    // optional arguments: gear rule, --dot=<path> and --csv=<path>
+    for argument in std::env::args().skip( 2 )
+    {
+        if let Some( path ) = argument.strip_prefix( "--dot=" )
+        {
+            dot_path = Some( path.to_string() );
+        }
+        else if let Some( path ) = argument.strip_prefix( "--csv=" )
+        {
+            csv_path = Some( path.to_string() );
+        }
+        else
+        {
+            gear_rule = GearRule::parse( &argument ).expect( "Invalid gear rule" );
+        }
+    }
+
+    let schematic = Schematic::parse( &input );
+
+    if let Some( path ) = dot_path
+    {
+        std::fs::write( path, export_dot( &schematic ) ).expect( "Failed to write DOT file" );
+    }
+    if let Some( path ) = csv_path
+    {
+        std::fs::write( path, export_csv( &schematic ) ).expect( "Failed to write CSV file" );
+    }
+
+    for index in schematic.isolated_numbers()
     {
-        part_number_sum += sum_part_numbers_from_line( line_index, &lines );
-        gear_ratio_sum  += sum_gear_ratios_from_line ( line_index, &lines );
+        let number = &schematic.numbers[ index ];
+        println!( "Not a part number: {} at row {}, cols {}..={}", number.value, number.row, number.col_begin, number.col_end - 1 );
     }
 
-    println!( "Total sum of part numbers: {}", part_number_sum );
-    println!( "Total sum of gear ratios: {}", gear_ratio_sum );
+    println!( "Total sum of part numbers: {}", sum_part_numbers( &schematic ) );
+    println!( "Total sum of gear ratios: {}", sum_gear_ratios( &schematic, &gear_rule ) );
 }
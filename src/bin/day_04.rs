@@ -1,77 +1,275 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
-fn to_set( number_list: &str ) -> HashSet< usize >
+struct Card
 {
-    number_list.trim().split_whitespace().map
-    (
-        | strnum: &str | -> usize
+    id             : usize,
+    winning_numbers: Vec< usize >,
+    my_numbers     : Vec< usize >,
+}
+
+#[derive( Debug, PartialEq )]
+enum CardError
+{
+    Malformed      { line: usize },
+    InvalidCardId  { line: usize, expected: usize },
+    InvalidNumber  { line: usize, number: String },
+    DuplicateNumber{ card: usize, number: usize },
+}
+
+impl fmt::Display for CardError
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {
+        match self
         {
-            strnum.trim().parse().unwrap()
+            CardError::Malformed      { line }           => write!( f, "line {}: malformed card", line ),
+            CardError::InvalidCardId  { line, expected } => write!( f, "line {}: invalid card number, expected {}", line, expected ),
+            CardError::InvalidNumber  { line, number }   => write!( f, "line {}: invalid number '{}'", line, number ),
+            CardError::DuplicateNumber{ card, number }   => write!( f, "card {}: number {} listed more than once", card, number ),
         }
-    ).collect()
+    }
 }
 
-fn get_new_scratchcards( line: &str ) -> usize
+fn parse_numbers( number_list: &str, line: usize, card: usize ) -> Result< Vec< usize >, CardError >
 {
-    let     points_desc = line.split( ':' ).nth( 1 ).unwrap();
-    let mut numbers_desc = points_desc.split( '|' );
+    let mut seen = HashSet::new();
+    let mut numbers = Vec::new();
 
-    let winning_numbers = numbers_desc.next().unwrap().trim();
-    let my_numbers      = numbers_desc.next().unwrap().trim();
+    for strnum in number_list.split_whitespace()
+    {
+        let number: usize = strnum.parse().map_err( | _ | CardError::InvalidNumber { line, number: strnum.to_string() } )?;
 
-    let winning_numbers_set = to_set( winning_numbers );
-    let my_numbers_set      = to_set( my_numbers      );
+        if !seen.insert( number )
+        {
+            return Err( CardError::DuplicateNumber { card, number } );
+        }
 
-    let intersection = winning_numbers_set.intersection( &my_numbers_set );
+        numbers.push( number );
+    }
 
-    intersection.count()
+    Ok( numbers )
 }
 
+// cards have to be numbered 1, 2, 3, ... since copies are handed out to the following cards
+fn parse_card( line: &str, line_number: usize, expected_id: usize ) -> Result< Card, CardError >
+{
+    let ( card_desc, numbers_desc ) = line.split_once( ':' ).ok_or( CardError::Malformed { line: line_number } )?;
 
-fn main()
+    let id = card_desc.strip_prefix( "Card" ).and_then( | id | id.trim().parse::< usize >().ok() );
+
+    if id != Some( expected_id )
+    {
+        return Err( CardError::InvalidCardId { line: line_number, expected: expected_id } );
+    }
+
+    let ( winning_numbers, my_numbers ) = numbers_desc.split_once( '|' ).ok_or( CardError::Malformed { line: line_number } )?;
+
+    Ok
+    (
+        Card
+        {
+            id             : expected_id,
+            winning_numbers: parse_numbers( winning_numbers, line_number, expected_id )?,
+            my_numbers     : parse_numbers( my_numbers     , line_number, expected_id )?,
+        }
+    )
+}
+
+fn parse_cards( input: &str ) -> Result< Vec< Card >, CardError >
 {
-    let file_path = std::env::args().nth( 1 ).unwrap();
-    let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
+    input.lines().enumerate().map( | ( index, line ) | parse_card( line, index + 1, index + 1 ) ).collect()
+}
+
+impl Card
+{
+    fn matches( &self ) -> usize
+    {
+        self.my_numbers.iter().filter( | number | self.winning_numbers.contains( number ) ).count()
+    }
+}
+
+#[derive( Debug, Clone, Copy, PartialEq )]
+enum Scoring
+{
+    Doubling, // 2^(n-1)
+    Linear,   // n
+}
 
-    let mut total_points       = 0;
-    let mut total_scratchcards = 0;
+#[derive( Debug, Clone, Copy, PartialEq )]
+enum CopyWindow
+{
+    Matches,          // next n cards
+    Scaled( usize ),  // next n * k cards
+    Fixed ( usize ),  // next k cards, if there is at least one match
+}
+
+#[derive( Debug, Clone, Copy, PartialEq )]
+struct RewardRules
+{
+    scoring: Scoring,
+    window : CopyWindow,
+}
+
+impl RewardRules
+{
+    fn standard() -> RewardRules
+    {
+        RewardRules { scoring: Scoring::Doubling, window: CopyWindow::Matches }
+    }
 
-    let mut pending_copies:  VecDeque< usize > = VecDeque::new();
+    fn points( &self, matches: usize ) -> usize
+    {
+        match ( self.scoring, matches )
+        {
+            ( _                , 0 ) => 0,
+            ( Scoring::Doubling, n ) => 2_usize.pow( ( n - 1 ) as u32 ),
+            ( Scoring::Linear  , n ) => n,
+        }
+    }
 
-    for game in input.lines()
+    fn window( &self, matches: usize ) -> usize
     {
-        let my_copies = 1 + match pending_copies.pop_front()
+        match ( self.window, matches )
         {
-            None => { 0 },
-            Some( x ) => { x },
-        };
+            ( _                      , 0 ) => 0,
+            ( CopyWindow::Matches    , n ) => n,
+            ( CopyWindow::Scaled( k ), n ) => n * k,
+            ( CopyWindow::Fixed ( k ), _ ) => k,
+        }
+    }
 
-        total_scratchcards += my_copies;
-        let new_scratchcards = get_new_scratchcards( game );
+    fn parse_scoring( description: &str ) -> Option< Scoring >
+    {
+        match description
+        {
+            "doubling" => Some( Scoring::Doubling ),
+            "linear"   => Some( Scoring::Linear   ),
+            _          => None,
+        }
+    }
 
-        if new_scratchcards > 0
+    // "matches", "scaled:<k>" or "fixed:<k>"
+    fn parse_window( description: &str ) -> Option< CopyWindow >
+    {
+        match description.split_once( ':' )
         {
-            total_points += 2_usize.pow( ( new_scratchcards - 1 ) as u32 );
+            None if description == "matches" => Some( CopyWindow::Matches ),
+            Some( ( "scaled", k ) )          => Some( CopyWindow::Scaled( k.parse().ok()? ) ),
+            Some( ( "fixed" , k ) )          => Some( CopyWindow::Fixed ( k.parse().ok()? ) ),
+            _                                => None,
         }
+    }
+}
 
-        // update pendine copies
-        let mut to_update = new_scratchcards;
+struct CardReport
+{
+    id     : usize,
+    matches: usize,
+    points : usize,
+    copies : usize,                       // including the original card
+    sources: BTreeMap< usize, usize >,    // card id -> number of copies it generated for this card
+}
 
-        for element in pending_copies.iter_mut()
+fn simulate( cards: &[ Card ], rules: &RewardRules ) -> Vec< CardReport >
+{
+    let mut reports: Vec< CardReport > = cards.iter().map
+    (
+        | card |
         {
-            if to_update == 0 { break; }
-            *element += my_copies;
-            to_update -= 1;
+            let matches = card.matches();
+            CardReport { id: card.id, matches, points: rules.points( matches ), copies: 1, sources: BTreeMap::new() }
         }
+    ).collect();
+
+    for index in 0 .. reports.len()
+    {
+        let copies = reports[ index ].copies;
+        let id     = reports[ index ].id;
+        let window = rules.window( reports[ index ].matches );
 
-        // add new copies to queue
-        for _ in 0 .. to_update
+        // copies past the last card are lost
+        for report in reports.iter_mut().skip( index + 1 ).take( window )
         {
-            pending_copies.push_back( my_copies );
+            report.copies += copies;
+            *report.sources.entry( id ).or_insert( 0 ) += copies;
         }
     }
 
+    reports
+}
+
+#[cfg(test)]
+const TEST_CARDS: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+#[test]
+fn test_simulation()
+{
+    let cards   = parse_cards( TEST_CARDS ).unwrap();
+    let reports = simulate( &cards, &RewardRules::standard() );
+
+    assert_eq!( reports.iter().map( | r | r.points ).sum::< usize >(), 13 );
+    assert_eq!( reports.iter().map( | r | r.copies ).sum::< usize >(), 30 );
+    assert_eq!( reports[ 3 ].copies, 8 );
+    assert_eq!( reports[ 3 ].sources, BTreeMap::from( [ ( 1, 1 ), ( 2, 2 ), ( 3, 4 ) ] ) );
+
+    let rules   = RewardRules { scoring: Scoring::Linear, window: CopyWindow::Fixed( 1 ) };
+    let reports = simulate( &cards, &rules );
+
+    assert_eq!( reports.iter().map( | r | r.points ).sum::< usize >(), 4 + 2 + 2 + 1 );
+    assert_eq!( reports.iter().map( | r | r.copies ).collect::< Vec< _ > >(), vec![ 1, 2, 3, 4, 5, 1 ] );
+
+    assert_eq!( RewardRules::parse_window( "scaled:2" ), Some( CopyWindow::Scaled( 2 ) ) );
+    assert_eq!( RewardRules::parse_window( "matches"  ), Some( CopyWindow::Matches ) );
+    assert_eq!( RewardRules::parse_window( "fixed:x"  ), None );
+}
+
+#[test]
+fn test_card_errors()
+{
+    assert_eq!( parse_cards( "Card 1: 1 2 | 3\nCard 3: 1 | 2" ).err(), Some( CardError::InvalidCardId { line: 2, expected: 2 } ) );
+    assert_eq!( parse_cards( "Card 1: 1 2 1 | 3" ).err(), Some( CardError::DuplicateNumber { card: 1, number: 1 } ) );
+    assert_eq!( parse_cards( "Card 1: 1 2 | 3 3" ).err(), Some( CardError::DuplicateNumber { card: 1, number: 3 } ) );
+    assert_eq!( parse_cards( "Card 1: 1 x | 3" ).err(), Some( CardError::InvalidNumber { line: 1, number: "x".to_string() } ) );
+    assert_eq!( parse_cards( "Card 1: 1 2 3" ).err(), Some( CardError::Malformed { line: 1 } ) );
+}
+
+fn main()
+{
+    let file_path = std::env::args().nth( 1 ).unwrap();
+    let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
+
+    let mut rules = RewardRules::standard();
+
+    if let Some( scoring ) = std::env::args().nth( 2 )
+    {
+        rules.scoring = RewardRules::parse_scoring( &scoring ).expect( "Unknown scoring, use doubling or linear" );
+    }
+    if let Some( window ) = std::env::args().nth( 3 )
+    {
+        rules.window = RewardRules::parse_window( &window ).expect( "Unknown copy window, use matches, scaled:<k> or fixed:<k>" );
+    }
+
+    let cards   = parse_cards( &input ).unwrap_or_else( | error | panic!( "Invalid scratchcards: {}", error ) );
+    let reports = simulate( &cards, &rules );
+
+    for report in &reports
+    {
+        let sources: Vec< String > = report.sources.iter().map( | ( id, copies ) | format!( "card {}: {}", id, copies ) ).collect();
+
+        print!( "Card {}: {} matches, {} points, {} copies", report.id, report.matches, report.points, report.copies );
+
+        if sources.is_empty() { println!(); } else { println!( " (from {})", sources.join( ", " ) ); }
+    }
+
+    let total_points      : usize = reports.iter().map( | report | report.points ).sum();
+    let total_scratchcards: usize = reports.iter().map( | report | report.copies ).sum();
+
     println!( "Total points: {}", total_points );
     println!( "Total scratchcards: {}", total_scratchcards );
 }
-
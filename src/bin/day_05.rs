@@ -1,303 +1,903 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
+use utils::input::{integers, key_value, numbered_blocks};
+
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
 struct Range
 {
     start: usize,
     len  : usize,
 }
 
+impl Range
+{
+    // one past the last number, which is past usize::MAX for a range ending at usize::MAX
+    fn end( &self ) -> u128
+    {
+        self.start as u128 + self.len as u128
+    }
+}
+
+// one past the largest number, the exclusive end of the domain
+const DOMAIN_END: u128 = usize::MAX as u128 + 1;
+
 struct MapRange
 {
     start: usize,
     dest : usize,
     len  : usize,
-}
-
-struct MappedRange
-{
-    mapped  : Option< Range >,
-    unmapped: Option< Vec< Range > >,
+    line : usize,
 }
 
 impl MapRange
 {
-    fn map_number( &self, number: usize ) -> Option< usize >
+    fn new( line: &str, line_number: usize ) -> MapRange
     {
-        if number >= self.start && number < self.start + self.len
+        let nums: Vec< usize > = integers( line ).unwrap();
+
+        let [ dest, start, len ] = nums[ .. ] else { panic!( "Invalid map range: {}", line ) };
+
+        MapRange
         {
-            let offset = number - self.start;
-            Some( self.dest + offset )
+            start,
+            dest,
+            len,
+            line: line_number,
         }
-        else
+    }
+
+    // number of values actually mapped - both the source and the destination are clamped
+    // to the end of the 0 ..= usize::MAX domain, less than len if either would overflow
+    fn clamped_len( &self ) -> u128
+    {
+        ( self.len as u128 ).min( DOMAIN_END - self.start as u128 ).min( DOMAIN_END - self.dest as u128 )
+    }
+}
+
+struct Map< 'a >
+{
+    map_ranges: Vec< MapRange >,
+    destination: &'a str,
+}
+
+impl Map< '_ >
+{
+    fn new( header_line: &str ) -> ( Map< '_ >, &str )
+    {
+        let ( map_name, _ ) = header_line.split_once( ' ' ).unwrap();
+
+        let mut mappings = map_name.splitn( 3, '-' );
+
+        let source = mappings.next().unwrap();
+                     mappings.next(); // consume "to"
+        let dest   = mappings.next().unwrap();
+
+        let map = Map { map_ranges: Vec::new(), destination: dest };
+
+        ( map, source )
+    }
+
+    // source ranges of this single map that end up in the given destination range
+    fn inverse_range( &self, range: Range ) -> Vec< Range >
+    {
+        PiecewiseLinear::from_map( self ).preimage( range )
+    }
+}
+
+// maps every x in start .. end to x + offset, end is u128 so that usize::MAX can be covered
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+struct Piece
+{
+    start : usize,
+    end   : u128,
+    offset: i128,
+}
+
+// Sorted, disjoint pieces covering the whole 0 ..= usize::MAX domain - numbers that a map does
+// not mention are covered by pieces with a zero offset.
+#[derive( Debug, Clone, PartialEq, Eq )]
+struct PiecewiseLinear
+{
+    pieces: Vec< Piece >,
+}
+
+impl PiecewiseLinear
+{
+    fn identity() -> PiecewiseLinear
+    {
+        PiecewiseLinear { pieces: vec![ Piece { start: 0, end: DOMAIN_END, offset: 0 } ] }
+    }
+
+    // merges neighbouring pieces with the same offset
+    fn normalized( pieces: Vec< Piece > ) -> PiecewiseLinear
+    {
+        let mut result: Vec< Piece > = Vec::with_capacity( pieces.len() );
+
+        for piece in pieces.into_iter().filter( | piece | ( piece.start as u128 ) < piece.end )
         {
-            None
+            match result.last_mut()
+            {
+                Some( last ) if last.end == piece.start as u128 && last.offset == piece.offset => last.end = piece.end,
+                _                                                                              => result.push( piece ),
+            }
         }
+
+        PiecewiseLinear { pieces: result }
     }
 
-    fn map_range( &self, range: Range ) -> MappedRange
+    // when map ranges overlap, the first one in the file wins
+    fn from_map( map: &Map ) -> PiecewiseLinear
     {
-        // range is fully left or fully right of mapping range o
-        if range.start + range.len < self.start || range.start >= self.start + self.len
+        let mut breakpoints: Vec< u128 > = vec![ 0, DOMAIN_END ];
+
+        // overflowing ranges are clamped to the end of the domain
+        for range in &map.map_ranges
         {
-            return MappedRange
-            {
-                mapped: None,
-                unmapped: Some( vec![ range ] ),
-            };
+            breakpoints.push( range.start as u128 );
+            breakpoints.push( range.start as u128 + range.clamped_len() );
         }
-        // range has left overlap with mapping range
-        else if range.start < self.start && range.start + range.len >= self.start && range.start + range.len < self.start + self.len 
+
+        breakpoints.sort();
+        breakpoints.dedup();
+
+        let pieces = breakpoints.windows( 2 ).map
+        (
+            | bounds |
+            {
+                let offset = map.map_ranges.iter()
+                    .find( | range | bounds[ 0 ] >= range.start as u128 && bounds[ 0 ] < range.start as u128 + range.clamped_len() )
+                    .map_or( 0, | range | range.dest as i128 - range.start as i128 );
+
+                Piece { start: bounds[ 0 ] as usize, end: bounds[ 1 ], offset }
+            }
+        ).collect();
+
+        PiecewiseLinear::normalized( pieces )
+    }
+
+    fn piece_index( &self, number: usize ) -> usize
+    {
+        self.pieces.partition_point( | piece | piece.end <= number as u128 )
+    }
+
+    fn evaluate( &self, number: usize ) -> usize
+    {
+        let piece = &self.pieces[ self.piece_index( number ) ];
+
+        ( number as i128 + piece.offset ) as usize
+    }
+
+    fn map_range( &self, range: Range ) -> Vec< Range >
+    {
+        let mut result: Vec< Range > = Vec::new();
+
+        let range_end = range.end();
+
+        for piece in self.pieces[ self.piece_index( range.start ) .. ].iter().take_while( | piece | ( piece.start as u128 ) < range_end )
         {
-            return MappedRange
+            let start = piece.start.max( range.start );
+            let end   = piece.end  .min( range_end   );
+
+            if ( start as u128 ) < end
             {
-                mapped: Some( Range{ start: self.map_number( self.start ).unwrap(), len: range.start + range.len - self.start } ),
-                unmapped: Some( vec![ Range{ start: range.start, len: self.start - range.start } ] ),
-            };  
+                result.push( Range { start: ( start as i128 + piece.offset ) as usize, len: ( end - start as u128 ) as usize } );
+            }
         }
-        // range is fully withing mapping range
-        else if range.start >= self.start && range.start + range.len < self.start + self.len
+
+        result
+    }
+
+    // All numbers that are mapped into the given range, as sorted and merged source ranges.
+    // Gaps in the map are identity mapped, so they show up here as well.
+    fn preimage( &self, range: Range ) -> Vec< Range >
+    {
+        // i128, since the last piece ends past usize::MAX and offsets can be negative
+        let target_start = range.start as i128;
+        let target_end   = range.end() as i128;
+
+        let mut sources: Vec< Range > = Vec::new();
+
+        for piece in &self.pieces
         {
-            return MappedRange
+            let start = ( piece.start as i128 ).max( target_start - piece.offset );
+            let end   = ( piece.end   as i128 ).min( target_end   - piece.offset );
+
+            if start < end
             {
-                mapped: Some( Range{ start: self.map_number( range.start ).unwrap(), len: range.len } ),
-                unmapped: None,
-            };
+                sources.push( Range { start: start as usize, len: ( end - start ) as usize } );
+            }
         }
-        // range has right overlap with mapping range
-        else if range.start >= self.start && range.start < self.start + self.len && range.start + range.len >= self.start + self.len
+
+        merge_ranges( sources )
+    }
+
+    // composition: applies self first, then next
+    fn then( &self, next: &PiecewiseLinear ) -> PiecewiseLinear
+    {
+        let mut pieces: Vec< Piece > = Vec::new();
+
+        for piece in &self.pieces
         {
-            return MappedRange
+            let image_start = ( piece.start as i128 + piece.offset ) as usize;
+            let image_end   = ( piece.end   as i128 + piece.offset ) as u128;
+
+            for next_piece in next.pieces[ next.piece_index( image_start ) .. ].iter().take_while( | p | ( p.start as u128 ) < image_end )
             {
-                mapped: Some( Range{ start: self.map_number( range.start ).unwrap(), len: self.start + self.len - range.start } ),
-                unmapped: Some( vec![ Range{ start: self.start + self.len, len: range.start + range.len - self.start - self.len } ] ),
-            };
+                let start = next_piece.start.max( image_start );
+                let end   = next_piece.end  .min( image_end   );
+
+                pieces.push
+                (
+                    Piece
+                    {
+                        start : ( start as i128 - piece.offset ) as usize,
+                        end   : ( end   as i128 - piece.offset ) as u128,
+                        offset: piece.offset + next_piece.offset,
+                    }
+                );
+            }
         }
-        // range is larger than mapping range
-        else if range.start < self.start && range.start + range.len >= self.start + self.len
+
+        PiecewiseLinear::normalized( pieces )
+    }
+
+    // intervals on which the two functions map numbers differently, with both offsets
+    fn differences( &self, other: &PiecewiseLinear ) -> Vec< ( Piece, Piece ) >
+    {
+        let mut result = Vec::new();
+
+        let mut breakpoints: Vec< u128 > = self.pieces.iter().chain( &other.pieces ).map( | piece | piece.start as u128 ).collect();
+        breakpoints.push( DOMAIN_END );
+        breakpoints.sort();
+        breakpoints.dedup();
+
+        for bounds in breakpoints.windows( 2 )
         {
-            return MappedRange
+            let start = bounds[ 0 ] as usize;
+
+            let offset       = self .pieces[ self .piece_index( start ) ].offset;
+            let other_offset = other.pieces[ other.piece_index( start ) ].offset;
+
+            if offset != other_offset
             {
-                mapped: Some( Range{ start: self.dest, len: self.len } ),
-                unmapped: Some
+                result.push
                 (
-                    vec!
-                    [
-                        Range{ start: range.start, len: self.start - range.start },
-                        Range{ start: self.start + self.len, len: range.start + range.len - self.start - self.len },
-                    ]
-                ),
-            };
+                    (
+                        Piece { start, end: bounds[ 1 ], offset },
+                        Piece { start, end: bounds[ 1 ], offset: other_offset },
+                    )
+                );
+            }
         }
 
-        panic!( "unhandled case!" );
+        result
     }
 
-    fn new( line: &str ) -> MapRange
+    // reads back the output of Display - one "start end offset" line per piece
+    fn parse( text: &str ) -> Option< PiecewiseLinear >
     {
-        let mut nums = line.splitn(3, ' ');
-
-        let dest  = nums.next().unwrap().parse().unwrap();
-        let start = nums.next().unwrap().parse().unwrap();
-        let len   = nums.next().unwrap().parse().unwrap();
+        let mut pieces: Vec< Piece > = Vec::new();
 
-        MapRange
+        for line in text.lines()
         {
-            start: start,
-            dest: dest,
-            len: len,
+            let mut values = line.split_whitespace();
+
+            let start  = values.next()?.parse().ok()?;
+            let end    = values.next()?.parse().ok()?;
+            let offset = values.next()?.parse().ok()?;
+
+            pieces.push( Piece { start, end, offset } );
         }
+
+        // pieces have to be a partition of the whole domain
+        let covers_domain = pieces.first()?.start == 0 &&
+                            pieces.last()?.end == DOMAIN_END &&
+                            pieces.windows( 2 ).all( | p | p[ 0 ].end == p[ 1 ].start as u128 );
+
+        covers_domain.then( || PiecewiseLinear::normalized( pieces ) )
     }
 }
 
-struct Map< 'a >
+impl fmt::Display for PiecewiseLinear
 {
-    map_ranges: Vec< MapRange >,
-    destination: &'a str,
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {
+        for piece in &self.pieces
+        {
+            writeln!( f, "{} {} {}", piece.start, piece.end, piece.offset )?;
+        }
+
+        Ok( () )
+    }
 }
 
-impl Map< '_ >
+// sorts the ranges and merges the overlapping or touching ones
+fn merge_ranges( mut ranges: Vec< Range > ) -> Vec< Range >
 {
-    fn new( header_line: &str ) -> ( Map, &str )
-    {
-        let ( map_name, _ ) = header_line.split_once( ' ' ).unwrap();
+    ranges.sort_by_key( | range | range.start );
 
-        let mut mappings = map_name.splitn( 3, '-' );
+    let mut merged: Vec< Range > = Vec::with_capacity( ranges.len() );
 
-        let source = mappings.next().unwrap();
-                     mappings.next(); // consume "to"
-        let dest   = mappings.next().unwrap();
+    for range in ranges
+    {
+        match merged.last_mut()
+        {
+            Some( last ) if last.end() >= range.start as u128 =>
+            {
+                let end  = last.end().max( range.end() );
+                last.len = ( end - last.start as u128 ) as usize;
+            },
+            _ => merged.push( range ),
+        }
+    }
 
-        let map = Map { map_ranges: Vec::new(), destination: dest };
+    merged
+}
 
-        ( map, source )
-    }
+#[derive( Debug, PartialEq )]
+enum AlmanacError
+{
+    DuplicateSource{ category: String },
+    Cycle          { category: String },
+    UnknownCategory{ category: String },
+    MissingLink    { from: String, to: String, category: String },
+}
 
-    fn map_number( &self, number: usize ) -> usize
+impl fmt::Display for AlmanacError
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
     {
-        for range in &self.map_ranges
+        match self
         {
-            let maybe_mapped = range.map_number( number );
-            if let Some( x ) = maybe_mapped
-            {
-                return x;
-            }
+            AlmanacError::DuplicateSource{ category }           => write!( f, "more than one map from category '{}'", category ),
+            AlmanacError::Cycle          { category }           => write!( f, "maps form a cycle through category '{}'", category ),
+            AlmanacError::UnknownCategory{ category }           => write!( f, "unknown category '{}'", category ),
+            AlmanacError::MissingLink    { from, to, category } => write!( f, "no route from '{}' to '{}', no map from category '{}'", from, to, category ),
         }
-
-        number
     }
+}
+
+// Problems with single map ranges - the almanac still works with them, but they are most likely mistakes.
+#[derive( Debug, PartialEq )]
+enum RangeIssue
+{
+    ZeroLength             { line: usize },
+    Overflow               { line: usize },
+    OverlappingSources     { lines: ( usize, usize ) }, // the earlier line wins
+    OverlappingDestinations{ lines: ( usize, usize ) }, // the map is not injective
+}
 
-    fn map_numbers( &self, numbers: &[usize] ) -> Vec< usize >
+impl fmt::Display for RangeIssue
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
     {
-        let number_mapper = | x: &usize | { self.map_number( *x ) };
-        numbers.iter().map( number_mapper ).collect()
+        match self
+        {
+            RangeIssue::ZeroLength             { line }                     => write!( f, "line {}: zero length range", line ),
+            RangeIssue::Overflow               { line }                     => write!( f, "line {}: range end overflows", line ),
+            RangeIssue::OverlappingSources     { lines: ( first, second ) } => write!( f, "lines {} and {}: overlapping source ranges", first, second ),
+            RangeIssue::OverlappingDestinations{ lines: ( first, second ) } => write!( f, "lines {} and {}: overlapping destination ranges", first, second ),
+        }
     }
+}
 
-    fn map_range( &self, range: Range ) -> Vec< Range >
+fn overlaps( start: usize, other_start: usize, len: usize, other_len: usize ) -> bool
+{
+    let ( start, other_start ) = ( start as u128, other_start as u128 );
+
+    start < other_start + other_len as u128 && other_start < start + len as u128
+}
+
+impl Map< '_ >
+{
+    fn validate( &self ) -> Vec< RangeIssue >
     {
-        let mut result: Vec< Range > = Vec::new();
+        let mut issues = Vec::new();
 
-        let mut unmapped: VecDeque< Range > = VecDeque::new();
-        unmapped.push_back( range );
+        for range in &self.map_ranges
+        {
+            if range.len == 0
+            {
+                issues.push( RangeIssue::ZeroLength { line: range.line } );
+            }
+            if range.clamped_len() < range.len as u128
+            {
+                issues.push( RangeIssue::Overflow { line: range.line } );
+            }
+        }
 
-        for map_range in &self.map_ranges
+        // only well formed ranges are compared with each other
+        let valid: Vec< &MapRange > = self.map_ranges.iter().filter( | r | r.len > 0 && r.clamped_len() == r.len as u128 ).collect();
+
+        for ( index, first ) in valid.iter().enumerate()
         {
-            let mut new_unmapped: VecDeque< Range > = VecDeque::new();
-            while let Some( unmapped_range ) = unmapped.pop_front()
+            for second in &valid[ index + 1 .. ]
             {
-                let mapped_range = map_range.map_range( unmapped_range );
+                let lines = ( first.line, second.line );
 
-                if let Some( mapped_range ) = mapped_range.mapped
+                if overlaps( first.start, second.start, first.len, second.len )
                 {
-                    result.push( mapped_range );
+                    issues.push( RangeIssue::OverlappingSources { lines } );
                 }
-                if let Some( unmapped_ranges ) = mapped_range.unmapped
+                if overlaps( first.dest, second.dest, first.len, second.len )
                 {
-                    for unmapped_range in unmapped_ranges
-                    {
-                        new_unmapped.push_back( unmapped_range );
-                    }
+                    issues.push( RangeIssue::OverlappingDestinations { lines } );
                 }
             }
-            unmapped = new_unmapped;
-        }
-        
-        // no mapping for those
-        for unmapped_range in unmapped
-        {
-            result.push( unmapped_range );
         }
 
-        result 
+        issues
     }
 }
 
+// Every category has at most one map to another category, so the maps form chains. Once validated,
+// those chains are free of cycles and walking them always ends.
 struct PuzzleInput< 'a >
 {
     seeds: Vec< usize >,
-    mappings: HashMap< &'a str, Map< 'a > >,
+    mappings: BTreeMap< &'a str, Map< 'a > >,
 }
 
 impl PuzzleInput< '_ >
 {
-    fn new( input: &str ) -> PuzzleInput
+    fn new( input: &str ) -> Result< PuzzleInput< '_ >, AlmanacError >
     {
-        let mut lines = input.lines();
+        let mut sections = numbered_blocks( input );
 
-        // first line, seeds
-        let seed_line = lines.next().unwrap();
-
-        let ( _, seed_desc ) = seed_line.split_once( ':' ).unwrap();
+        // first section, seeds
+        let ( _, seed_desc ) = key_value( sections.next().unwrap().1 ).unwrap();
 
         let mut parsed_input = PuzzleInput
         {
-            seeds: seed_desc.split_whitespace().map( str::parse::< usize > ).map( Result::unwrap ).collect(),
-            mappings: HashMap::new()
+            seeds: integers( seed_desc ).unwrap(),
+            mappings: BTreeMap::new()
         };
 
-        let mut current_line = lines.next();
-
-        while let Some( cur_lin ) = current_line
+        // every other section is a map header followed by map ranges
+        for ( first_line, section ) in sections
         {
-            if cur_lin.is_empty()
+            let mut lines = section.lines();
+
+            let ( mut map, source ) = Map::new( lines.next().unwrap() );
+
+            map.map_ranges.extend( lines.enumerate().map( | ( index, line ) | MapRange::new( line, first_line + 1 + index ) ) );
+
+            if parsed_input.mappings.insert( source, map ).is_some()
             {
-                current_line = lines.next();
-                continue;
+                return Err( AlmanacError::DuplicateSource { category: source.to_string() } );
             }
+        }
+
+        parsed_input.check_cycles()?;
 
-            // parse mappings
-            let ( mut map, source ) = Map::new( cur_lin );
+        Ok( parsed_input )
+    }
 
-            // parse map ranges
-            current_line = lines.next();
+    fn check_cycles( &self ) -> Result< (), AlmanacError >
+    {
+        for start in self.mappings.keys()
+        {
+            let mut visited: BTreeSet< &str > = BTreeSet::new();
+            let mut category = *start;
 
-            while let Some( cur_lin ) = current_line
+            while let Some( map ) = self.mappings.get( category )
             {
-                if cur_lin.is_empty()
+                if !visited.insert( category )
                 {
-                    break;
+                    return Err( AlmanacError::Cycle { category: category.to_string() } );
                 }
+                category = map.destination;
+            }
+        }
+
+        Ok( () )
+    }
+
+    // range issues of all maps, ordered by line
+    fn validate( &self ) -> Vec< RangeIssue >
+    {
+        let mut issues: Vec< RangeIssue > = self.mappings.values().flat_map( | map | map.validate() ).collect();
 
-                map.map_ranges.push( MapRange::new( cur_lin ) );
-                current_line = lines.next();
+        issues.sort_by_key
+        (
+            | issue | match issue
+            {
+                RangeIssue::ZeroLength             { line  } => ( *line, *line ),
+                RangeIssue::Overflow               { line  } => ( *line, *line ),
+                RangeIssue::OverlappingSources     { lines } => *lines,
+                RangeIssue::OverlappingDestinations{ lines } => *lines,
             }
+        );
+
+        issues
+    }
+
+    fn categories( &self ) -> BTreeSet< &str >
+    {
+        self.mappings.iter().flat_map( | ( source, map ) | [ *source, map.destination ] ).collect()
+    }
 
-            parsed_input.mappings.insert( source, map );
+    // maps to apply, in order, to get from one category to the other
+    fn route( &self, from: &str, to: &str ) -> Result< Vec< &Map< '_ > >, AlmanacError >
+    {
+        let categories = self.categories();
+
+        for category in [ from, to ]
+        {
+            if !categories.contains( category )
+            {
+                return Err( AlmanacError::UnknownCategory { category: category.to_string() } );
+            }
+        }
+
+        let mut chain: Vec< &Map > = Vec::new();
+        let mut category = from;
+
+        while category != to
+        {
+            let category_mapping = self.mappings.get( category ).ok_or
+            (
+                AlmanacError::MissingLink { from: from.to_string(), to: to.to_string(), category: category.to_string() }
+            )?;
+
+            chain.push( category_mapping );
+            category = category_mapping.destination;
         }
 
-        parsed_input
+        Ok( chain )
+    }
+
+    // single function mapping one category straight to the other
+    fn compose( &self, from: &str, to: &str ) -> Result< PiecewiseLinear, AlmanacError >
+    {
+        let composed = self.route( from, to )?.iter().fold
+        (
+            PiecewiseLinear::identity(),
+            | composed, map | composed.then( &PiecewiseLinear::from_map( map ) )
+        );
+
+        Ok( composed )
+    }
+
+    // walks the chain backwards, one map at a time - yields the same ranges as compose( from, to ).preimage( range )
+    fn inverse_chain( &self, from: &str, to: &str, range: Range ) -> Result< Vec< Range >, AlmanacError >
+    {
+        let ranges = self.route( from, to )?.iter().rev().fold
+        (
+            vec![ range ],
+            | ranges, map | merge_ranges( ranges.iter().flat_map( | r | map.inverse_range( *r ) ).collect() )
+        );
+
+        Ok( ranges )
+    }
+}
+
+#[cfg(test)]
+const TEST_ALMANAC: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+#[test]
+fn test_composition()
+{
+    let puzzle_input = PuzzleInput::new( TEST_ALMANAC ).unwrap();
+
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap();
+
+    let locations: Vec< _ > = puzzle_input.seeds.iter().map( | seed | composed.evaluate( *seed ) ).collect();
+    assert_eq!( locations, vec![ 82, 43, 86, 35 ] );
+
+    let ranges = composed.map_range( Range { start: 79, len: 14 } );
+    assert_eq!( ranges.iter().map( | r | r.len ).sum::< usize >(), 14 );
+    assert_eq!( ranges.iter().map( | r | r.start ).min(), Some( 46 ) );
+
+    // composing step by step and in one go must agree with evaluating map after map
+    let soil = PiecewiseLinear::from_map( &puzzle_input.mappings[ "seed" ] );
+    for seed in 0 .. 120
+    {
+        assert_eq!( composed.evaluate( seed ), puzzle_input.compose( "soil", "location" ).unwrap().evaluate( soil.evaluate( seed ) ) );
     }
 }
 
+#[test]
+fn test_inverse_mapping()
+{
+    let puzzle_input = PuzzleInput::new( TEST_ALMANAC ).unwrap();
+
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap();
+
+    let below_47 = Range { start: 0, len: 47 };
+    let seeds    = composed.preimage( below_47 );
+
+    for seed in 0 .. 200
+    {
+        let in_preimage = seeds.iter().any( | r | seed >= r.start && seed < r.start + r.len );
+        assert_eq!( in_preimage, composed.evaluate( seed ) < 47, "seed {}", seed );
+    }
+
+    assert!( seeds.iter().any( | r | 82 >= r.start && 82 < r.start + r.len ) );
+
+    assert_eq!( puzzle_input.inverse_chain( "seed", "location", below_47 ), Ok( seeds ) );
+
+    // past the last map range everything is identity mapped, up to the open ended last piece
+    assert_eq!( composed.preimage( Range { start: 1000, len: 10 } ), vec![ Range { start: 1000, len: 10 } ] );
+
+    // seed-to-soil: 98, 99 -> 50, 51 and 50 .. 97 -> 52 .. 99, everything else is identity mapped
+    let seed_to_soil = &puzzle_input.mappings[ "seed" ];
+    assert_eq!( seed_to_soil.inverse_range( Range { start: 50, len: 3 } ), vec![ Range { start: 50, len: 1 }, Range { start: 98, len: 2 } ] );
+    assert_eq!( seed_to_soil.inverse_range( Range { start: 99, len: 2 } ), vec![ Range { start: 97, len: 1 }, Range { start: 100, len: 1 } ] );
+    assert_eq!( seed_to_soil.inverse_range( Range { start: 40, len: 5 } ), vec![ Range { start: 40, len: 5 } ] );
+}
+
+#[test]
+fn test_category_routing()
+{
+    let puzzle_input = PuzzleInput::new( TEST_ALMANAC ).unwrap();
+
+    let route: Vec< &str > = puzzle_input.route( "soil", "humidity" ).unwrap().iter().map( | map | map.destination ).collect();
+    assert_eq!( route, vec![ "fertilizer", "water", "light", "temperature", "humidity" ] );
+
+    assert_eq!( puzzle_input.compose( "light", "light" ), Ok( PiecewiseLinear::identity() ) );
+    assert_eq!( puzzle_input.compose( "seed", "dirt" ), Err( AlmanacError::UnknownCategory { category: "dirt".to_string() } ) );
+
+    // maps only go forward
+    assert_eq!
+    (
+        puzzle_input.compose( "location", "seed" ),
+        Err( AlmanacError::MissingLink { from: "location".to_string(), to: "seed".to_string(), category: "location".to_string() } )
+    );
+
+    let duplicate = "seeds: 1\n\na-to-b map:\n0 0 1\n\na-to-c map:\n0 0 1";
+    assert_eq!( PuzzleInput::new( duplicate ).err(), Some( AlmanacError::DuplicateSource { category: "a".to_string() } ) );
+
+    let cycle = "seeds: 1\n\na-to-b map:\n0 0 1\n\nb-to-c map:\n0 0 1\n\nc-to-b map:\n0 0 1";
+    assert_eq!( PuzzleInput::new( cycle ).err(), Some( AlmanacError::Cycle { category: "b".to_string() } ) );
+}
+
+#[test]
+fn test_validation()
+{
+    assert!( PuzzleInput::new( TEST_ALMANAC ).unwrap().validate().is_empty() );
+
+    let almanac = "seeds: 1
+
+a-to-b map:
+0 10 5
+100 12 5
+100 50 0
+2 18446744073709551610 10
+
+b-to-c map:
+0 0 10
+5 20 10";
+
+    let puzzle_input = PuzzleInput::new( almanac ).unwrap();
+
+    assert_eq!
+    (
+        puzzle_input.validate(),
+        vec!
+        [
+            RangeIssue::OverlappingSources     { lines: ( 4, 5 ) },
+            RangeIssue::ZeroLength             { line: 6 },
+            RangeIssue::Overflow               { line: 7 },
+            RangeIssue::OverlappingDestinations{ lines: ( 10, 11 ) },
+        ]
+    );
+
+    // the earlier range wins, overflowing ones are clamped
+    let composed = puzzle_input.compose( "a", "b" ).unwrap();
+    assert_eq!( composed.evaluate( 13 ), 3 );
+    assert_eq!( composed.evaluate( usize::MAX - 1 ), 6 );
+
+    // an overflowing destination is clamped the same way, the rest of the range is identity mapped
+    let puzzle_input = PuzzleInput::new( "seeds: 1\n\na-to-b map:\n18446744073709551610 0 10" ).unwrap();
+
+    assert_eq!( puzzle_input.validate(), vec![ RangeIssue::Overflow { line: 4 } ] );
+
+    let composed = puzzle_input.compose( "a", "b" ).unwrap();
+    assert_eq!( composed.evaluate( 4 ), usize::MAX - 1 );
+    assert_eq!( composed.evaluate( 5 ), usize::MAX );
+    assert_eq!( composed.evaluate( 6 ), 6 );
+}
+
+#[test]
+fn test_largest_seed()
+{
+    // usize::MAX is part of the domain, both as a single seed and at the end of a seed range
+    let almanac = "seeds: 18446744073709551615 1
+
+seed-to-location map:
+0 18446744073709551614 2";
+
+    let puzzle_input = PuzzleInput::new( almanac ).unwrap();
+
+    assert!( puzzle_input.validate().is_empty() );
+
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap();
+
+    assert_eq!( composed.evaluate( usize::MAX ), 1 );
+    assert_eq!( composed.evaluate( 1 ), 1 );
+    assert_eq!( PiecewiseLinear::identity().evaluate( usize::MAX ), usize::MAX );
+
+    assert_eq!( composed.map_range( Range { start: usize::MAX, len: 1 } ), vec![ Range { start: 1, len: 1 } ] );
+    assert_eq!
+    (
+        composed.preimage( Range { start: 1, len: 1 } ),
+        vec![ Range { start: 1, len: 1 }, Range { start: usize::MAX, len: 1 } ]
+    );
+
+    // the domain ends past usize::MAX, so the last piece has to end there as well
+    assert_eq!( composed.then( &composed ).evaluate( usize::MAX ), 1 );
+    assert_eq!( PiecewiseLinear::parse( &composed.to_string() ), Some( composed ) );
+    assert_eq!( PiecewiseLinear::parse( "0 18446744073709551615 0\n" ), None );
+}
+
+#[test]
+fn test_large_values()
+{
+    // sources and destinations above 2^63, the offset between them does not fit in i64
+    let almanac = "seeds: 10000000000000000000 5
+
+seed-to-location map:
+9000000000000000000 10000000000000000000 100";
+
+    let puzzle_input = PuzzleInput::new( almanac ).unwrap();
+
+    // everything that validates must also compose
+    assert!( puzzle_input.validate().is_empty() );
+
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap();
+
+    assert_eq!( composed.evaluate( 10000000000000000000 ), 9000000000000000000 );
+    assert_eq!( composed.evaluate( 10000000000000000099 ), 9000000000000000099 );
+    assert_eq!( composed.evaluate( 10000000000000000100 ), 10000000000000000100 );
+
+    let ranges = composed.map_range( Range { start: 10000000000000000000, len: 5 } );
+    assert_eq!( ranges, vec![ Range { start: 9000000000000000000, len: 5 } ] );
+
+    let seeds = composed.preimage( Range { start: 9000000000000000000, len: 5 } );
+    assert_eq!( seeds, vec![ Range { start: 9000000000000000000, len: 5 }, Range { start: 10000000000000000000, len: 5 } ] );
+
+    // going through the map twice keeps the offsets exact
+    assert_eq!( composed.then( &composed ).evaluate( 10000000000000000000 ), 9000000000000000000 );
+    assert_eq!( PiecewiseLinear::parse( &composed.to_string() ), Some( composed ) );
+}
+
+#[test]
+fn test_cache_round_trip()
+{
+    let puzzle_input = PuzzleInput::new( TEST_ALMANAC ).unwrap();
+
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap();
+
+    assert_eq!( PiecewiseLinear::parse( &composed.to_string() ), Some( composed.clone() ) );
+    assert_eq!( PiecewiseLinear::parse( "0 10 5\n" ), None );
+
+    assert!( composed.differences( &composed ).is_empty() );
+
+    let differences = composed.differences( &PiecewiseLinear::identity() );
+    assert!( differences.iter().all( | ( piece, identity ) | piece.offset != 0 && identity.offset == 0 ) );
+}
 
 fn main()
 {
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let puzzle_input = PuzzleInput::new( &input );
+    let puzzle_input = PuzzleInput::new( &input ).unwrap_or_else( | error | panic!( "Invalid almanac: {}", error ) );
 
-    // part 1
-    let mut category = "seed";
-    let mut values   = puzzle_input.seeds.clone();
+    // with --strict, suspicious map ranges are errors instead of warnings
+    let strict = std::env::args().skip( 2 ).any( | argument | argument == "--strict" );
+    let issues = puzzle_input.validate();
 
-    while category != "location"
+    for issue in &issues
     {
-        let category_mapping = &puzzle_input.mappings[ category ];
+        println!( "{}: {}", if strict { "Error" } else { "Warning" }, issue );
+    }
 
-        values   = category_mapping.map_numbers( &values );
-        category = category_mapping.destination;
+    if strict && !issues.is_empty()
+    {
+        panic!( "Invalid almanac: {} suspicious map ranges", issues.len() );
     }
 
-    let min_location = values.iter().min().unwrap();
+    let composed = puzzle_input.compose( "seed", "location" ).unwrap_or_else( | error | panic!( "Invalid almanac: {}", error ) );
 
-    println!( "Part 1 minimum location is: {}", min_location );
+    // optional arguments: --print, --save=<path>, --compare=<path of a previously saved composition>,
+    // --below=<location> listing the seed ranges that end up at locations below the given one
+    // and --map=<from>:<to> printing the composed mapping between any two categories
+    for argument in std::env::args().skip( 2 )
+    {
+        if argument == "--strict"
+        {
+            continue;
+        }
+        else if argument == "--print"
+        {
+            print!( "{}", composed );
+        }
+        else if let Some( path ) = argument.strip_prefix( "--save=" )
+        {
+            std::fs::write( path, composed.to_string() ).expect( "Failed to write composed mapping" );
+        }
+        else if let Some( path ) = argument.strip_prefix( "--compare=" )
+        {
+            let saved = std::fs::read_to_string( path ).expect( "Failed to read composed mapping" );
+            let other = PiecewiseLinear::parse( &saved ).expect( "Invalid composed mapping" );
 
-    // part 2
-    let mut ranges: Vec< Range > = puzzle_input.seeds.chunks_exact( 2 ).map
-    (
-        | chunk: &[usize] |
+            for ( piece, other_piece ) in composed.differences( &other )
+            {
+                println!( "{} .. {}: offset {} vs {}", piece.start, piece.end, piece.offset, other_piece.offset );
+            }
+        }
+        else if let Some( location ) = argument.strip_prefix( "--below=" )
         {
-            Range{ start: chunk[ 0 ], len: chunk[ 1 ] }
+            let location: usize = location.parse().expect( "Invalid location" );
+
+            for range in puzzle_input.inverse_chain( "seed", "location", Range { start: 0, len: location } ).unwrap()
+            {
+                println!( "Seeds {} .. {} end up below location {}", range.start, range.end(), location );
+            }
         }
-    ).collect();
-    
-    let mut category = "seed";
-    while category != "location"
-    {
-        let category_mapping = &puzzle_input.mappings[ category ];
+        else if let Some( categories ) = argument.strip_prefix( "--map=" )
+        {
+            let ( from, to ) = categories.split_once( ':' ).expect( "Expected --map=<from>:<to>" );
+
+            let route = puzzle_input.route( from, to ).unwrap_or_else( | error | panic!( "{}", error ) );
 
-        let mut new_ranges: Vec< Range > = Vec::new();
+            let path: Vec< &str > = std::iter::once( from ).chain( route.iter().map( | map | map.destination ) ).collect();
+            println!( "Route: {}", path.join( " -> " ) );
 
-        for range in ranges
+            print!( "{}", puzzle_input.compose( from, to ).unwrap() );
+        }
+        else
         {
-            new_ranges.extend( category_mapping.map_range( range ) );
+            panic!( "Unknown argument: {}", argument );
         }
-
-        category = category_mapping.destination;
-        ranges = new_ranges;
     }
 
-    let min_location = ranges.iter().min_by( | x, y | { x.start.cmp( &y.start ) } ).unwrap().start;
+    // part 1
+    let min_location = puzzle_input.seeds.iter().map( | seed | composed.evaluate( *seed ) ).min().unwrap();
+
+    println!( "Part 1 minimum location is: {}", min_location );
+
+    // part 2
+    let min_location = puzzle_input.seeds.chunks_exact( 2 )
+        .flat_map( | chunk | composed.map_range( Range { start: chunk[ 0 ], len: chunk[ 1 ] } ) )
+        .map( | range | range.start )
+        .min()
+        .unwrap();
 
     println!( "Part 2 minimum location is: {}", min_location );
 }
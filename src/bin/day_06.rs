@@ -1,50 +1,160 @@
+use std::ops::RangeInclusive;
 
-fn num_solutions( time: usize, record_distance: usize ) -> usize
-{
-    let ftime = time as f64;
-    let fdist = record_distance as f64;
-    let lower_bound = ( ftime - ( ( ftime * ftime - 4f64 * fdist ) as f64 ).sqrt() ) / 2f64;
-    let upper_bound = ( ftime + ( ( ftime * ftime - 4f64 * fdist ) as f64 ).sqrt() ) / 2f64;
+use utils::input::{integers, key_value};
+use utils::math::range_where_product_exceeds;
 
-    let lower_bound = if lower_bound.ceil() == lower_bound { lower_bound + 1f64 } else { lower_bound.ceil() } as usize;
-    let upper_bound = if upper_bound.floor() == upper_bound { upper_bound - 1f64 } else { upper_bound.floor() } as usize;
+// how the numbers on the sheet are read
+#[derive( Debug, Clone, Copy, PartialEq )]
+enum ParseMode
+{
+    Columns, // every column is a separate race
+    Kerning, // the spaces are bad kerning, the whole line is a single number
+}
 
-    println!( "For time={}, record={}, num_solutions={}", time, record_distance, upper_bound - lower_bound + 1 );
+impl ParseMode
+{
+    fn parse( description: &str ) -> Option< ParseMode >
+    {
+        match description
+        {
+            "columns" => Some( ParseMode::Columns ),
+            "kerning" => Some( ParseMode::Kerning ),
+            _         => None,
+        }
+    }
+}
 
-    upper_bound - lower_bound + 1
+#[derive( Debug, Clone, Copy, PartialEq )]
+struct Race
+{
+    time  : u64,
+    record: u64,
 }
 
-fn main()
+impl Race
 {
-    let file_path = std::env::args().nth( 1 ).unwrap();
-    let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
+    fn distance( &self, hold: u64 ) -> u128
+    {
+        hold as u128 * ( self.time - hold ) as u128
+    }
 
-    let mut lines = input.lines();
+    fn winning_holds( &self ) -> Option< RangeInclusive< u64 > >
+    {
+        range_where_product_exceeds( self.time, self.record )
+    }
+
+    fn num_solutions( &self ) -> u64
+    {
+        match self.winning_holds()
+        {
+            Some( holds ) => holds.end() - holds.start() + 1,
+            None          => 0,
+        }
+    }
 
-    let ( _, times ) = lines.next().unwrap().split_once( ':' ).unwrap();
-    let ( _, distances ) = lines.next().unwrap().split_once( ':' ).unwrap();
+    // for an odd time, holding one longer ties
+    fn optimal_hold( &self ) -> u64
+    {
+        self.time / 2
+    }
 
+    fn best_distance( &self ) -> u128
     {
-        let times = times.split_whitespace().map( str::parse::< usize > ).map( Result::unwrap );
-        let distances = distances.split_whitespace().map( str::parse::< usize > ).map( Result::unwrap );
+        self.distance( self.optimal_hold() )
+    }
+
+    // the smallest record nobody can beat
+    fn unwinnable_record( &self ) -> u128
+    {
+        self.best_distance()
+    }
+}
+
+fn parse_races( input: &str, mode: ParseMode ) -> Vec< Race >
+{
+    let mut lines = input.lines();
 
-        let mut part_01_solution: usize = 1;
+    let ( _, times     ) = key_value( lines.next().unwrap() ).unwrap();
+    let ( _, distances ) = key_value( lines.next().unwrap() ).unwrap();
 
-        for ( time, distance ) in times.zip( distances )
+    let numbers = | line: &str | -> Vec< u64 >
+    {
+        match mode
         {
-            let num_races = num_solutions( time, distance );
-            part_01_solution *= num_races;
+            ParseMode::Columns => integers( line ).unwrap(),
+            ParseMode::Kerning => vec![ line.split_whitespace().collect::< String >().parse().unwrap() ],
         }
+    };
 
-        println!( "Part 01 solution: {}", part_01_solution );
-    }
+    numbers( times ).into_iter().zip( numbers( distances ) ).map( | ( time, record ) | Race { time, record } ).collect()
+}
+
+#[cfg(test)]
+const TEST_SHEET: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+#[test]
+fn test_races()
+{
+    let races = parse_races( TEST_SHEET, ParseMode::Columns );
+
+    assert_eq!( races.iter().map( Race::num_solutions ).collect::< Vec< _ > >(), vec![ 4, 8, 9 ] );
+    assert_eq!( races[ 0 ].winning_holds(), Some( 2 ..= 5 ) );
+    assert_eq!( ( races[ 0 ].optimal_hold(), races[ 0 ].best_distance() ), ( 3, 12 ) );
+
+    // a record of 12 can only be tied, not beaten
+    assert_eq!( races[ 0 ].unwinnable_record(), 12 );
+    assert_eq!( Race { time: 7, record: 12 }.num_solutions(), 0 );
+    assert_eq!( Race { time: 7, record: 11 }.num_solutions(), 2 );
+
+    let races = parse_races( TEST_SHEET, ParseMode::Kerning );
+
+    assert_eq!( races, vec![ Race { time: 71530, record: 940200 } ] );
+    assert_eq!( races[ 0 ].num_solutions(), 71503 );
+}
 
+fn main()
+{
+    let file_path = std::env::args().nth( 1 ).unwrap();
+    let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
+
+    // optional parse mode, columns or kerning - both parts are solved without it
+    let modes = match std::env::args().nth( 2 )
+    {
+        Some( mode ) => vec![ ParseMode::parse( &mode ).expect( "Unknown parse mode, use columns or kerning" ) ],
+        None         => vec![ ParseMode::Columns, ParseMode::Kerning ],
+    };
+
+    for mode in modes
     {
-        let single_time: usize = times.replace( " ", "" ).parse().unwrap();
-        let single_dist: usize = distances.replace( " ", "" ).parse().unwrap();
+        let races = parse_races( &input, mode );
 
-        let part_02_solution = num_solutions( single_time, single_dist );
+        for race in &races
+        {
+            print!( "Race time={}, record={}: ", race.time, race.record );
+
+            match race.winning_holds()
+            {
+                Some( holds ) => print!( "{} ways to win, holding {} ..= {}", race.num_solutions(), holds.start(), holds.end() ),
+                None          => print!( "no way to win" ),
+            }
+
+            println!
+            (
+                ", best distance {} holding {} ({} over the record), unwinnable from record {}",
+                race.best_distance(),
+                race.optimal_hold(),
+                race.best_distance() as i128 - race.record as i128,
+                race.unwinnable_record()
+            );
+        }
 
-        println!( "Part 02 solution: {}", part_02_solution );
+        let solution: u64 = races.iter().map( Race::num_solutions ).product();
+
+        match mode
+        {
+            ParseMode::Columns => println!( "Part 01 solution: {}", solution ),
+            ParseMode::Kerning => println!( "Part 02 solution: {}", solution ),
+        }
     }
 }
@@ -1,7 +1,37 @@
-use core::panic;
 use std::cmp::Ordering;
+use std::fmt;
 
-#[derive( PartialEq )]
+// Multiplicities of the distinct cards in a hand, largest first. Comparing them lexicographically
+// ranks hands of any size the same way the named five card types are ranked.
+#[derive( Debug, Clone, PartialEq, Eq, PartialOrd, Ord )]
+struct Signature( Vec< usize > );
+
+impl Signature
+{
+    // counts of the cards in the hand, in any order, zeros are ignored
+    fn from_counts( counts: &[ usize ] ) -> Signature
+    {
+        let mut counts: Vec< usize > = counts.iter().copied().filter( | count | *count > 0 ).collect();
+        counts.sort_unstable_by( | a, b | b.cmp( a ) );
+
+        Signature( counts )
+    }
+}
+
+impl fmt::Display for Signature
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {
+        match HandType::from_signature( self )
+        {
+            Some( hand_type ) => write!( f, "{:?}", hand_type ),
+            None              => write!( f, "{}", self.0.iter().map( usize::to_string ).collect::< Vec< _ > >().join( "-" ) ),
+        }
+    }
+}
+
+// names of the five card signatures
+#[derive( Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord )]
 enum HandType
 {
     HighCard,
@@ -13,196 +43,388 @@ enum HandType
     FiveOfAKind,
 }
 
-impl HandType 
+impl HandType
 {
-    fn as_num( &self ) -> usize
+    fn from_signature( signature: &Signature ) -> Option< HandType >
     {
-        match &self
+        match signature.0[ .. ]
         {
-            HandType::HighCard => { 1 },
-            HandType::OnePair => { 2 },
-            HandType::TwoPair => { 3 },
-            HandType::ThreeOfAKind => { 4 },
-            HandType::FullHouse => { 5 },
-            HandType::FourOfAKind => { 6 },
-            HandType::FiveOfAKind => { 7 },
+            [ 5 ]             => Some( HandType::FiveOfAKind  ),
+            [ 4, 1 ]          => Some( HandType::FourOfAKind  ),
+            [ 3, 2 ]          => Some( HandType::FullHouse    ),
+            [ 3, 1, 1 ]       => Some( HandType::ThreeOfAKind ),
+            [ 2, 2, 1 ]       => Some( HandType::TwoPair      ),
+            [ 2, 1, 1, 1 ]    => Some( HandType::OnePair      ),
+            [ 1, 1, 1, 1, 1 ] => Some( HandType::HighCard     ),
+            _                 => None,
         }
     }
 }
 
-struct Hand
+#[derive( Debug, PartialEq )]
+enum HandError
 {
-    cards       : Vec< char >,
-    bid         : usize,
-    hand_type   : HandType,
-    num_jokers  : usize,
+    Malformed  { line: usize },
+    InvalidBid { line: usize, bid: String },
+    WrongSize  { line: usize, expected: usize, found: usize },
+    UnknownCard{ line: usize, card: char },
 }
 
-fn card_index( card: char ) -> usize
+impl fmt::Display for HandError
 {
-    match card
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
     {
-        '2' ..= '9' => { card as usize - '2' as usize },
-        'T' => { 8 },
-        'J' => { 9 },
-        'Q' => { 10 },
-        'K' => { 11 },
-        'A' => { 12 },
-        _   => { panic!( "unknown card" ) }
+        match self
+        {
+            HandError::Malformed  { line }                  => write!( f, "line {}: malformed hand", line ),
+            HandError::InvalidBid { line, bid }             => write!( f, "line {}: invalid bid '{}'", line, bid ),
+            HandError::WrongSize  { line, expected, found } => write!( f, "line {}: expected {} cards, found {}", line, expected, found ),
+            HandError::UnknownCard{ line, card }            => write!( f, "line {}: card '{}' is not in the deck", line, card ),
+        }
     }
 }
 
-fn part2_card_index( card: char ) -> isize 
+// where wild cards rank when breaking ties between hands of the same type
+#[derive( Debug, Clone, Copy, PartialEq )]
+enum WildRank
+{
+    Natural, // their usual place in the rank order
+    Weakest, // below every other card
+}
+
+#[derive( Clone )]
+struct CardRules
 {
-    let index = card_index( card );
-    if index == card_index( 'J' )
+    order    : Vec< char >, // the deck, weakest to strongest
+    hand_size: usize,
+    wild     : Vec< char >, // stand in for any other card when classifying a hand
+    wild_rank: WildRank,
+}
+
+impl CardRules
+{
+    fn standard() -> CardRules
     {
-        return -1;
+        CardRules { order: "23456789TJQKA".chars().collect(), hand_size: 5, wild: Vec::new(), wild_rank: WildRank::Natural }
     }
-    else
+
+    // same deck and hand size, with J as the weakest wild card
+    fn with_jokers( &self ) -> CardRules
     {
-        return index as isize;
+        CardRules { wild: vec![ 'J' ], wild_rank: WildRank::Weakest, ..self.clone() }
     }
-}
 
-impl Hand
-{
-    fn new( cards: Vec< char >, bid: usize ) -> Hand
+    fn is_wild( &self, card: char ) -> bool
     {
-        let mut card_counts = [ 0usize; 13 ];
+        self.wild.contains( &card )
+    }
 
-        for card in cards.iter()
+    fn card_rank( &self, card: char ) -> usize
+    {
+        if self.is_wild( card ) && self.wild_rank == WildRank::Weakest
         {
-            card_counts[ card_index( *card ) ] += 1;  
+            return 0;
         }
 
-        let max_repeats = card_counts.iter().max().unwrap();
-        let num_jokers  = card_counts[ card_index( 'J' ) ];
+        1 + self.card_index( card )
+    }
 
-        let hand_type = match max_repeats
+    // cards are checked against the deck when parsing
+    fn card_index( &self, card: char ) -> usize
+    {
+        self.order.iter().position( | c | *c == card ).unwrap_or_else( || panic!( "unknown card {}", card ) )
+    }
+
+    fn natural_type( &self, cards: &[ char ] ) -> Signature
+    {
+        let mut counts = vec![ 0usize; self.order.len() ];
+
+        for card in cards
         {
-            5 => { HandType::FiveOfAKind },
-            4 => { HandType::FourOfAKind },
-            3 =>
-            {
-                if card_counts.iter().any( | &x | x == 2 )
-                {
-                    HandType::FullHouse
-                }
-                else
-                {
-                    HandType::ThreeOfAKind
-                }
-            },
-            2 =>
+            counts[ self.card_index( *card ) ] += 1;
+        }
+
+        Signature::from_counts( &counts )
+    }
+
+    // Best type reachable by substituting the wild cards. Adding to the largest group always beats
+    // spreading the wild cards out, so it is enough to try turning all of them into the same card.
+    fn hand_type( &self, cards: &[ char ] ) -> Signature
+    {
+        if !cards.iter().any( | card | self.is_wild( *card ) )
+        {
+            return self.natural_type( cards );
+        }
+
+        self.order.iter().filter( | candidate | !self.is_wild( **candidate ) ).map
+        (
+            | candidate |
             {
-                if card_counts.iter().filter( | &&x | x == 2 ).count() == 2
-                {
-                    HandType::TwoPair
-                }
-                else
-                {
-                    HandType::OnePair
-                }
-            },
-            _ => { HandType::HighCard }
-        };
+                let substituted: Vec< char > = cards.iter().map( | card | if self.is_wild( *card ) { *candidate } else { *card } ).collect();
+                self.natural_type( &substituted )
+            }
+        ).max().unwrap_or_else( || self.natural_type( cards ) )
+    }
 
-        Hand { cards, bid, hand_type, num_jokers }
+    // hands are ordered by type first, then card by card
+    fn sort_key( &self, cards: &[ char ] ) -> ( Signature, Vec< usize > )
+    {
+        ( self.hand_type( cards ), cards.iter().map( | card | self.card_rank( *card ) ).collect() )
     }
-}
 
-fn parse_hand( line: &str ) -> Hand
-{
-    let ( cards, bid ) = line.split_once( ' ' ).unwrap();
+    // which rule puts the first hand before, after or level with the second one
+    fn explain( &self, first: &[ char ], second: &[ char ] ) -> ( Ordering, Decision )
+    {
+        let ( first_type, first_ranks ) = self.sort_key( first );
+        let ( second_type, second_ranks ) = self.sort_key( second );
 
-    assert_eq!( cards.len(), 5 );
+        if first_type != second_type
+        {
+            return ( first_type.cmp( &second_type ), Decision::HandType { first: first_type, second: second_type } );
+        }
+
+        let differing = ( 0 .. first_ranks.len() ).find( | position | first_ranks[ *position ] != second_ranks[ *position ] );
 
-    Hand::new( cards.chars().collect(), bid.parse().unwrap() )
+        match differing
+        {
+            Some( position ) =>
+            (
+                first_ranks[ position ].cmp( &second_ranks[ position ] ),
+                Decision::Card { position, first: first[ position ], second: second[ position ] }
+            ),
+            None => ( Ordering::Equal, Decision::Tie ),
+        }
+    }
 }
 
-fn compare_hands( first: &Hand, second: &Hand ) -> Ordering
+#[derive( Debug, PartialEq )]
+enum Decision
 {
-    let val_first  = first.hand_type.as_num();
-    let val_second = second.hand_type.as_num();
+    HandType{ first: Signature, second: Signature },
+    Card    { position: usize, first: char, second: char }, // first differing card, hand types are equal
+    Tie,
+}
 
-    let comparison = val_first.cmp( &val_second );
-    if comparison == Ordering::Equal
+impl fmt::Display for Decision
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
     {
-        for ( &f, &s ) in first.cards.iter().zip( second.cards.iter() )
+        match self
         {
-            let c = card_index( f ).cmp( &card_index( s ) );
-            if c != Ordering::Equal { return c; }
+            Decision::HandType{ first, second }           => write!( f, "hand type {} vs {}", first, second ),
+            Decision::Card    { position, first, second } => write!( f, "same hand type, card {} is {} vs {}", position + 1, first, second ),
+            Decision::Tie                                 => write!( f, "hands rank the same" ),
         }
-        Ordering::Equal
-    }
-    else
-    {
-        comparison
     }
 }
 
-fn hand_strength( x: &Hand ) -> usize
+struct Hand
 {
-    match x.hand_type
+    cards: Vec< char >,
+    bid  : usize,
+}
+
+fn parse_cards( cards: &str, line_number: usize, rules: &CardRules ) -> Result< Vec< char >, HandError >
+{
+    let cards: Vec< char > = cards.chars().collect();
+
+    if cards.len() != rules.hand_size
     {
-        HandType::FiveOfAKind => { x.hand_type.as_num() },
-        HandType::FourOfAKind =>
-        {
-            if x.num_jokers > 0 { HandType::FiveOfAKind.as_num() }
-            else                { HandType::FourOfAKind.as_num() }
-        },
-        HandType::FullHouse   =>
-        {
-            if x.num_jokers > 0 { HandType::FiveOfAKind.as_num() }
-            else                { x.hand_type.as_num()           }
-        },
-        HandType::ThreeOfAKind =>
-        {
-            if x.num_jokers == 3 || x.num_jokers == 1 { HandType::FourOfAKind .as_num() }
-            else                                      { HandType::ThreeOfAKind.as_num() }
-        },
-        HandType::TwoPair =>
-        {
-                 if x.num_jokers == 1 { HandType::FullHouse  .as_num() }
-            else if x.num_jokers == 2 { HandType::FourOfAKind.as_num() }
-            else                      { HandType::TwoPair    .as_num() }
-        },
-        HandType::OnePair =>
-        {
-            if x.num_jokers > 0 { HandType::ThreeOfAKind.as_num() }
-            else                { HandType::OnePair     .as_num() }
-        },
-        HandType::HighCard => { x.hand_type.as_num() + x.num_jokers }
+        return Err( HandError::WrongSize { line: line_number, expected: rules.hand_size, found: cards.len() } );
     }
+
+    if let Some( card ) = cards.iter().find( | card | !rules.order.contains( card ) )
+    {
+        return Err( HandError::UnknownCard { line: line_number, card: *card } );
+    }
+
+    Ok( cards )
 }
 
-fn compare_hands2( first: &Hand, second: &Hand ) -> Ordering
+fn parse_hand( line: &str, line_number: usize, rules: &CardRules ) -> Result< Hand, HandError >
 {
-    let val_first  = hand_strength( first );
-    let val_second = hand_strength( second );
+    let ( cards, bid ) = line.trim().split_once( ' ' ).ok_or( HandError::Malformed { line: line_number } )?;
 
-    let comparison = val_first.cmp( &val_second );
-    if comparison == Ordering::Equal
-    {
-        // same number of jokers, do the high-card, but treat J as the weakest
-        for ( f, s ) in first.cards.iter().zip( second.cards.iter() )
+    let cards = parse_cards( cards, line_number, rules )?;
+
+    let bid = bid.trim().parse().map_err( | _ | HandError::InvalidBid { line: line_number, bid: bid.trim().to_string() } )?;
+
+    Ok( Hand { cards, bid } )
+}
+
+fn parse_hands( input: &str, rules: &CardRules ) -> Result< Vec< Hand >, HandError >
+{
+    input.lines().enumerate().map( | ( index, line ) | parse_hand( line, index + 1, rules ) ).collect()
+}
+
+struct HandReport
+{
+    rank    : usize,
+    cards   : String,
+    natural : Signature,     // without substituting wild cards
+    promoted: Signature,
+    tiebreak: Vec< usize >,  // card ranks, compared in order when the types are equal
+    winnings: usize,
+}
+
+// sorts the hands, like total_winnings, and describes every one of them
+fn report( hands: &mut [ Hand ], rules: &CardRules ) -> Vec< HandReport >
+{
+    hands.sort_by_cached_key( | hand | rules.sort_key( &hand.cards ) );
+
+    hands.iter().enumerate().map
+    (
+        | ( index, hand ) |
         {
-            let c = part2_card_index( *f ).cmp( &part2_card_index( *s ) );
-            if c != Ordering::Equal { return c; }
+            let ( promoted, tiebreak ) = rules.sort_key( &hand.cards );
+
+            HandReport
+            {
+                rank    : index + 1,
+                cards   : hand.cards.iter().collect(),
+                natural : rules.natural_type( &hand.cards ),
+                promoted,
+                tiebreak,
+                winnings: ( index + 1 ) * hand.bid,
+            }
         }
-        Ordering::Equal
+    ).collect()
+}
+
+fn total_winnings( hands: &mut [ Hand ], rules: &CardRules ) -> usize
+{
+    report( hands, rules ).iter().map( | r | r.winnings ).sum()
+}
+
+#[test]
+fn test_part2_comparisons()
+{
+    let rules = CardRules::standard().with_jokers();
+
+    let first  = parse_hand( "J8888 11", 1, &rules ).unwrap();
+    let second = parse_hand( "J9JAJ 99", 2, &rules ).unwrap();
+
+    assert_eq!( rules.sort_key( &first.cards ).cmp( &rules.sort_key( &second.cards ) ), Ordering::Greater );
+}
+
+// the joker promotion table used before the rules were generic, by natural type and number of jokers
+#[cfg(test)]
+fn joker_table( natural: HandType, num_jokers: usize ) -> HandType
+{
+    match ( natural, num_jokers )
+    {
+        ( _                     , 0     ) => natural,
+        ( HandType::FiveOfAKind , _     ) => HandType::FiveOfAKind,
+        ( HandType::FourOfAKind , _     ) => HandType::FiveOfAKind,
+        ( HandType::FullHouse   , _     ) => HandType::FiveOfAKind,
+        ( HandType::ThreeOfAKind, 1 | 3 ) => HandType::FourOfAKind,
+        ( HandType::ThreeOfAKind, _     ) => HandType::ThreeOfAKind,
+        ( HandType::TwoPair     , 1     ) => HandType::FullHouse,
+        ( HandType::TwoPair     , 2     ) => HandType::FourOfAKind,
+        ( HandType::TwoPair     , _     ) => HandType::TwoPair,
+        ( HandType::OnePair     , _     ) => HandType::ThreeOfAKind,
+        ( HandType::HighCard    , _     ) => HandType::OnePair,
     }
-    else
+}
+
+#[test]
+fn test_generic_rules_match_table()
+{
+    let standard = CardRules::standard();
+    let jokers   = CardRules::standard().with_jokers();
+
+    // every hand made of these cards, which covers every type with any number of jokers
+    let deck = [ '2', '3', '4', '5', 'J' ];
+
+    for index in 0 .. deck.len().pow( 5 )
     {
-        comparison
+        let cards: Vec< char > = ( 0 .. 5 ).map( | position | deck[ index / deck.len().pow( position ) % deck.len() ] ).collect();
+
+        let natural    = HandType::from_signature( &standard.hand_type( &cards ) ).unwrap();
+        let num_jokers = cards.iter().filter( | card | **card == 'J' ).count();
+
+        assert_eq!( HandType::from_signature( &jokers.hand_type( &cards ) ), Some( joker_table( natural, num_jokers ) ), "{:?}", cards );
     }
 }
 
 #[test]
-fn test_part2_comparisons()
+fn test_total_winnings()
 {
-    assert_eq!( compare_hands2( &parse_hand("J8888 11"), &parse_hand( "J9JAJ 99" ) ), Ordering::Greater );
+    let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+
+    let mut hands = parse_hands( input, &CardRules::standard() ).unwrap();
+
+    assert_eq!( total_winnings( &mut hands, &CardRules::standard() ), 6440 );
+    assert_eq!( total_winnings( &mut hands, &CardRules::standard().with_jokers() ), 5905 );
+}
+
+#[test]
+fn test_report_and_explain()
+{
+    let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+    let rules = CardRules::standard().with_jokers();
+
+    let mut hands = parse_hands( input, &rules ).unwrap();
+    let reports   = report( &mut hands, &rules );
+
+    assert_eq!( reports.iter().map( | r | r.winnings ).sum::< usize >(), 5905 );
+
+    let last = reports.last().unwrap();
+    assert_eq!( ( last.rank, last.cards.as_str(), last.winnings ), ( 5, "KTJJT", 5 * 220 ) );
+    assert_eq!( ( last.natural.to_string(), last.promoted.to_string() ), ( "TwoPair".to_string(), "FourOfAKind".to_string() ) );
+    assert_eq!( last.tiebreak, vec![ 12, 9, 0, 0, 9 ] );
+
+    let cards = | text: &str | parse_cards( text, 1, &rules ).unwrap();
+
+    assert_eq!
+    (
+        rules.explain( &cards( "QQQJA" ), &cards( "T55J5" ) ),
+        ( Ordering::Greater, Decision::Card { position: 0, first: 'Q', second: 'T' } )
+    );
+    assert_eq!
+    (
+        rules.explain( &cards( "32T3K" ), &cards( "KK677" ) ),
+        ( Ordering::Less, Decision::HandType { first: Signature( vec![ 2, 1, 1, 1 ] ), second: Signature( vec![ 2, 2, 1 ] ) } )
+    );
+    assert_eq!( rules.explain( &cards( "KK677" ), &cards( "KK677" ) ), ( Ordering::Equal, Decision::Tie ) );
+
+    // without jokers, J ranks above T
+    let standard = CardRules::standard();
+    assert_eq!( standard.explain( &cards( "JKKK2" ), &cards( "TKKK2" ) ).0, Ordering::Greater );
+    assert_eq!( rules   .explain( &cards( "JKKK2" ), &cards( "TKKKK" ) ), ( Ordering::Less, Decision::Card { position: 0, first: 'J', second: 'T' } ) );
+}
+
+#[test]
+fn test_other_hand_sizes()
+{
+    let rules = CardRules { order: "123456".chars().collect(), hand_size: 3, ..CardRules::standard() };
+
+    // three of a kind beats a pair beats high card, then card by card
+    let mut hands = parse_hands( "123 1\n611 10\n222 100\n511 1000", &rules ).unwrap();
+
+    assert_eq!( total_winnings( &mut hands, &rules ), 1 + 2 * 1000 + 3 * 10 + 4 * 100 );
+    assert_eq!( rules.hand_type( &[ '6', '1', '1' ] ), Signature( vec![ 2, 1 ] ) );
+
+    // seven cards: 3-3-1 beats 3-2-2 beats 3-2-1-1
+    let rules = CardRules { hand_size: 7, ..CardRules::standard().with_jokers() };
+
+    let keys: Vec< _ > = [ "2223344", "2223334", "2223345", "22233J4" ].iter().map( | cards | rules.sort_key( &cards.chars().collect::< Vec< _ > >() ) ).collect();
+    assert!( keys[ 1 ] > keys[ 0 ] && keys[ 0 ] > keys[ 2 ] );
+    assert_eq!( keys[ 1 ].0.to_string(), "3-3-1" );
+
+    // the joker joins the largest group
+    assert_eq!( keys[ 3 ].0.to_string(), "4-2-1" );
+}
+
+#[test]
+fn test_hand_errors()
+{
+    let rules = CardRules::standard();
+
+    assert_eq!( parse_hands( "32T3K 765\n32T3X 1", &rules ).err(), Some( HandError::UnknownCard { line: 2, card: 'X' } ) );
+    assert_eq!( parse_hands( "32T3 765", &rules ).err(), Some( HandError::WrongSize { line: 1, expected: 5, found: 4 } ) );
+    assert_eq!( parse_hands( "32T3K x", &rules ).err(), Some( HandError::InvalidBid { line: 1, bid: "x".to_string() } ) );
+    assert_eq!( parse_hands( "32T3K", &rules ).err(), Some( HandError::Malformed { line: 1 } ) );
 }
 
 fn main()
@@ -210,32 +432,65 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut hands: Vec< _ > = input.lines().map( | line | { parse_hand( line ) } ).collect();
-
-    {
-        let mut part_01_solution = 0usize;
+    let mut rules = CardRules::standard();
 
-        hands.sort_by( compare_hands );
+    let mut show_report = false;
+    let mut explain: Option< String > = None;
 
-        for ( index, hand ) in hands.iter().enumerate()
+    // optional arguments: --deck=<cards, weakest first>, --hand-size=<number of cards>, --report listing
+    // how every hand was ranked and --explain=<cards>,<cards> telling what decides the order of two hands
+    for argument in std::env::args().skip( 2 )
+    {
+        if let Some( deck ) = argument.strip_prefix( "--deck=" )
         {
-            part_01_solution += ( index + 1 ) * hand.bid;
+            rules.order = deck.chars().collect();
+        }
+        else if let Some( hand_size ) = argument.strip_prefix( "--hand-size=" )
+        {
+            rules.hand_size = hand_size.parse().expect( "Invalid hand size" );
+        }
+        else if argument == "--report"
+        {
+            show_report = true;
+        }
+        else if let Some( hands ) = argument.strip_prefix( "--explain=" )
+        {
+            explain = Some( hands.to_string() );
+        }
+        else
+        {
+            panic!( "Unknown argument: {}", argument );
         }
-
-        println!( "Part 01 solution: {}", part_01_solution );
     }
 
+    let mut hands = parse_hands( &input, &rules ).unwrap_or_else( | error | panic!( "Invalid hands: {}", error ) );
+
+    for ( part, rules ) in [ ( 1, rules.clone() ), ( 2, rules.with_jokers() ) ]
     {
-        let mut part_02_solution = 0usize;
+        if let Some( explain ) = &explain
+        {
+            let ( first, second ) = explain.split_once( ',' ).expect( "Expected --explain=<cards>,<cards>" );
 
-        hands.sort_by( compare_hands2 );
+            let first  = parse_cards( first , 1, &rules ).unwrap_or_else( | error | panic!( "Invalid hand: {}", error ) );
+            let second = parse_cards( second, 2, &rules ).unwrap_or_else( | error | panic!( "Invalid hand: {}", error ) );
 
-        for ( index, hand ) in hands.iter().enumerate()
+            let ( ordering, decision ) = rules.explain( &first, &second );
+
+            println!( "Part {:02}: {:?}, decided by {}", part, ordering, decision );
+        }
+
+        if show_report
         {
-            println!( "Rank {}, hand: {}, strength: {}", index + 1, hand.cards.iter().collect::< String >(), hand_strength( hand ) );
-            part_02_solution += ( index + 1 ) * hand.bid;
+            for r in report( &mut hands, &rules )
+            {
+                println!
+                (
+                    "Rank {}, hand: {}, type: {}, promoted: {}, tiebreak: {:?}, winnings: {}",
+                    r.rank, r.cards, r.natural, r.promoted, r.tiebreak, r.winnings
+                );
+            }
         }
 
-        println!( "Part 02 solution: {}", part_02_solution );
+        println!( "Part {:02} solution: {}", part, total_winnings( &mut hands, &rules ) );
     }
 }
@@ -1,6 +1,14 @@
-use std::{collections::HashMap, str::Chars};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 
-use num_integer::lcm;
+use utils::math::{checked_lcm_multiple, Congruence, CongruenceError};
+
+#[derive( Debug, Clone, Copy, PartialEq )]
+enum Instruction
+{
+    Left,
+    Right,
+}
 
 struct Node< 'a >
 {
@@ -10,115 +18,824 @@ struct Node< 'a >
 
 type Map< 'a > = HashMap< &'a str, Node< 'a > >;
 
-fn parse_map< 'a, Iter >( lines: Iter ) -> Option< Map< 'a > >
-where
-    Iter: Iterator< Item = &'a str >,
+struct Network< 'a >
+{
+    instructions: Vec< Instruction >,
+    map         : Map< 'a >, // every referenced node is guaranteed to exist
+}
+
+#[derive( Debug, PartialEq )]
+enum NetworkError
+{
+    NoInstructions,
+    UnknownInstruction{ position: usize, instruction: char },
+    Malformed         { line: usize },
+    DuplicateNode     { line: usize, node: String },
+    MissingNode       { line: usize, node: String },
+}
+
+impl fmt::Display for NetworkError
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {
+        match self
+        {
+            NetworkError::NoInstructions                              => write!( f, "no instructions" ),
+            NetworkError::UnknownInstruction{ position, instruction } => write!( f, "unknown instruction '{}' at position {}", instruction, position + 1 ),
+            NetworkError::Malformed         { line }                  => write!( f, "line {}: malformed node", line ),
+            NetworkError::DuplicateNode     { line, node }            => write!( f, "line {}: node {} defined more than once", line, node ),
+            NetworkError::MissingNode       { line, node }            => write!( f, "line {}: node {} is not defined", line, node ),
+        }
+    }
+}
+
+fn parse_instructions( line: &str ) -> Result< Vec< Instruction >, NetworkError >
+{
+    line.chars().enumerate().map
+    (
+        | ( position, instruction ) | match instruction
+        {
+            'L' => Ok( Instruction::Left  ),
+            'R' => Ok( Instruction::Right ),
+            _   => Err( NetworkError::UnknownInstruction { position, instruction } ),
+        }
+    ).collect()
+}
+
+fn parse_network( input: &str ) -> Result< Network< '_ >, NetworkError >
 {
-    let mut result = Map::new();
+    let mut lines = input.lines().enumerate().filter( | ( _, line ) | !line.trim().is_empty() );
 
-    for line in lines
+    let ( _, instructions ) = lines.next().ok_or( NetworkError::NoInstructions )?;
+
+    let instructions = parse_instructions( instructions.trim() )?;
+
+    if instructions.is_empty()
     {
-        let ( key, value ) = line.split_once( '=' )?;
+        return Err( NetworkError::NoInstructions );
+    }
 
-        let key = key.trim();
-        let value = value.trim();
+    let mut map = Map::new();
+    let mut references: Vec< ( usize, &str ) > = Vec::new();
 
-        let ( left, right ) = value[ 1 .. value.len() - 1 ].split_once( ',' )?;
+    for ( index, line ) in lines
+    {
+        let line_number = index + 1;
+
+        let ( key, value ) = line.split_once( '=' ).ok_or( NetworkError::Malformed { line: line_number } )?;
+
+        let ( left, right ) = value.trim()
+            .strip_prefix( '(' )
+            .and_then( | value | value.strip_suffix( ')' ) )
+            .and_then( | value | value.split_once( ',' ) )
+            .ok_or( NetworkError::Malformed { line: line_number } )?;
+
+        let ( key, left, right ) = ( key.trim(), left.trim(), right.trim() );
+
+        if map.insert( key, Node { left, right } ).is_some()
+        {
+            return Err( NetworkError::DuplicateNode { line: line_number, node: key.to_string() } );
+        }
+
+        references.push( ( line_number, left  ) );
+        references.push( ( line_number, right ) );
+    }
 
-        result.insert( key, Node { left, right: right.trim_start() } );
+    // nodes may be referenced before they are defined, so this can only be checked at the end
+    if let Some( ( line, node ) ) = references.iter().find( | ( _, node ) | !map.contains_key( node ) )
+    {
+        return Err( NetworkError::MissingNode { line: *line, node: node.to_string() } );
     }
 
-    Some( result )
+    Ok( Network { instructions, map } )
 }
 
-fn part_01_follow_instructions( instructions: Chars, map: &Map ) -> usize
+// The network with the node names interned into dense ids, ordered by name.
+struct Graph< 'a >
 {
-    let mut num_steps = 0usize;
+    names       : Vec< &'a str >,
+    instructions: Vec< Instruction >,
+    left        : Vec< usize >,
+    right       : Vec< usize >,
+    full_pass   : Vec< usize >, // node reached by following every instruction once
+}
+
+impl< 'a > Graph< 'a >
+{
+    fn new( network: &Network< 'a > ) -> Graph< 'a >
+    {
+        let mut names: Vec< &str > = network.map.keys().copied().collect();
+        names.sort();
+
+        let ids: HashMap< &str, usize > = names.iter().enumerate().map( | ( id, name ) | ( *name, id ) ).collect();
+
+        let left  = names.iter().map( | name | ids[ network.map[ name ].left  ] ).collect();
+        let right = names.iter().map( | name | ids[ network.map[ name ].right ] ).collect();
+
+        let mut graph = Graph { names, instructions: network.instructions.clone(), left, right, full_pass: Vec::new() };
+
+        graph.full_pass = ( 0 .. graph.num_nodes() ).map
+        (
+            | id | graph.instructions.iter().fold( id, | node, instruction | graph.step( node, *instruction ) )
+        ).collect();
+
+        graph
+    }
+
+    fn num_nodes( &self ) -> usize
+    {
+        self.names.len()
+    }
+
+    fn step( &self, node: usize, instruction: Instruction ) -> usize
+    {
+        match instruction
+        {
+            Instruction::Left  => self.left [ node ],
+            Instruction::Right => self.right[ node ],
+        }
+    }
 
-    let mut char_iter = instructions.cycle();
-    let mut cur_node  = "AAA";
+    fn matching( &self, pattern: &str ) -> Vec< bool >
+    {
+        self.names.iter().map( | name | matches_pattern( pattern, name ) ).collect()
+    }
 
-    if !map.contains_key( cur_node )
+    // nodes reachable through any left or right edges, regardless of the instructions
+    fn reachable( &self, start: usize ) -> Vec< bool >
     {
-        return num_steps;
+        let mut reached = vec![ false; self.num_nodes() ];
+        let mut stack   = vec![ start ];
+
+        reached[ start ] = true;
+
+        while let Some( node ) = stack.pop()
+        {
+            for next in [ self.left[ node ], self.right[ node ] ]
+            {
+                if !reached[ next ]
+                {
+                    reached[ next ] = true;
+                    stack.push( next );
+                }
+            }
+        }
+
+        reached
     }
 
-    while cur_node != "ZZZ"
+    // nodes actually visited when following the instructions forever
+    fn visited( &self, start: usize ) -> Vec< bool >
     {
-        num_steps += 1;
+        let mut visited     = vec![ false; self.num_nodes() ];
+        let mut pass_starts = vec![ false; self.num_nodes() ];
 
-        let instruction = char_iter.next().unwrap();
+        let mut node = start;
 
-        if instruction == 'L' { cur_node = map[ cur_node ].left ; }
-        else                  { cur_node = map[ cur_node ].right; }
+        // once a pass starts at the same node again, everything after it repeats
+        while !pass_starts[ node ]
+        {
+            pass_starts[ node ] = true;
+
+            for instruction in &self.instructions
+            {
+                visited[ node ] = true;
+                node = self.step( node, *instruction );
+            }
+        }
+
+        visited
     }
+}
 
-    num_steps
+struct Reachability< 'a >
+{
+    start        : &'a str,
+    num_reachable: usize,
+    num_visited  : usize,
+    goals        : Vec< &'a str >, // reachable goal nodes
+    visited_goals: Vec< &'a str >,
 }
 
-fn part_02_follow_instructions( instructions: Chars, map: &Map ) -> usize
+fn reached_by_any_start( graph: &Graph, is_start: &[ bool ] ) -> Vec< bool >
 {
-    let current_nodes: Vec< _ > = map.keys().filter
+    let mut reached_by_any = vec![ false; graph.num_nodes() ];
+
+    for start in ( 0 .. graph.num_nodes() ).filter( | node | is_start[ *node ] )
+    {
+        for ( any, reached ) in reached_by_any.iter_mut().zip( graph.reachable( start ) )
+        {
+            *any |= reached;
+        }
+    }
+
+    reached_by_any
+}
+
+// summary for every start, and the nodes no start can reach
+fn reachability< 'a >( graph: &Graph< 'a >, is_start: &[ bool ], is_goal: &[ bool ] ) -> ( Vec< Reachability< 'a > >, Vec< &'a str > )
+{
+    let goal_names = | set: &[ bool ] | -> Vec< &'a str >
+    {
+        ( 0 .. graph.num_nodes() ).filter( | node | set[ *node ] && is_goal[ *node ] ).map( | node | graph.names[ node ] ).collect()
+    };
+
+    let summaries = ( 0 .. graph.num_nodes() ).filter( | node | is_start[ *node ] ).map
     (
-        | x | x.ends_with( 'A' )
+        | start |
+        {
+            let reachable = graph.reachable( start );
+            let visited   = graph.visited( start );
+
+            Reachability
+            {
+                start        : graph.names[ start ],
+                num_reachable: reachable.iter().filter( | r | **r ).count(),
+                num_visited  : visited.iter().filter( | v | **v ).count(),
+                goals        : goal_names( &reachable ),
+                visited_goals: goal_names( &visited ),
+            }
+        }
     ).collect();
 
-    let mut individual_num_steps: Vec< usize > = Vec::new();
+    let reached_by_any = reached_by_any_start( graph, is_start );
+
+    let unreachable = ( 0 .. graph.num_nodes() ).filter( | node | !reached_by_any[ *node ] ).map( | node | graph.names[ node ] ).collect();
+
+    ( summaries, unreachable )
+}
+
+// starts and goals are filled, nodes no start can reach are dashed
+// node names can be any text, so quotes and backslashes have to be escaped inside DOT strings
+fn dot_escape( text: &str ) -> String
+{
+    text.replace( '\\', "\\\\" ).replace( '"', "\\\"" )
+}
+
+fn export_dot( graph: &Graph, is_start: &[ bool ], is_goal: &[ bool ] ) -> String
+{
+    let reached_by_any = reached_by_any_start( graph, is_start );
+
+    let mut dot = String::from( "digraph network {\n" );
+
+    for ( node, name ) in graph.names.iter().enumerate()
+    {
+        let fill_color = if is_start[ node ] { Some( "palegreen" ) } else if is_goal[ node ] { Some( "lightcoral" ) } else { None };
+
+        // graphviz keeps only the last style attribute, so all styles go into a single one
+        let mut styles: Vec< &str > = Vec::new();
+
+        if fill_color.is_some()
+        {
+            styles.push( "filled" );
+        }
+        if !reached_by_any[ node ]
+        {
+            styles.push( "dashed" );
+        }
+
+        let mut attributes: Vec< String > = Vec::new();
+
+        if !styles.is_empty()
+        {
+            attributes.push( format!( "style=\"{}\"", styles.join( "," ) ) );
+        }
+        if let Some( color ) = fill_color
+        {
+            attributes.push( format!( "fillcolor={}", color ) );
+        }
+
+        if attributes.is_empty()
+        {
+            dot += &format!( "    \"{}\";\n", dot_escape( name ) );
+        }
+        else
+        {
+            dot += &format!( "    \"{}\" [{}];\n", dot_escape( name ), attributes.join( ", " ) );
+        }
+    }
+
+    for ( node, name ) in graph.names.iter().enumerate()
+    {
+        let ( left, right ) = ( graph.left[ node ], graph.right[ node ] );
+
+        let ( name, left_name, right_name ) = ( dot_escape( name ), dot_escape( graph.names[ left ] ), dot_escape( graph.names[ right ] ) );
+
+        if left == right
+        {
+            dot += &format!( "    \"{}\" -> \"{}\" [label=\"L/R\"];\n", name, left_name );
+        }
+        else
+        {
+            dot += &format!( "    \"{}\" -> \"{}\" [label=\"L\"];\n", name, left_name  );
+            dot += &format!( "    \"{}\" -> \"{}\" [label=\"R\"];\n", name, right_name );
+        }
+    }
+
+    dot += "}\n";
+
+    dot
+}
+
+// Glob style pattern for node names - '?' matches any single character and '*' any number of them.
+fn matches_pattern( pattern: &str, node: &str ) -> bool
+{
+    let mut pattern_chars = pattern.chars();
+
+    match pattern_chars.next()
+    {
+        None        => node.is_empty(),
+        Some( '*' ) => node.char_indices().map( | ( index, _ ) | index ).chain( [ node.len() ] ).any
+        (
+            | index | matches_pattern( pattern_chars.as_str(), &node[ index .. ] )
+        ),
+        Some( '?' ) =>
+        {
+            let mut node_chars = node.chars();
+            node_chars.next().is_some() && matches_pattern( pattern_chars.as_str(), node_chars.as_str() )
+        },
+        Some( c )   => node.strip_prefix( c ).is_some_and( | rest | matches_pattern( pattern_chars.as_str(), rest ) ),
+    }
+}
+
+// Walk of a single ghost. The state after every step is ( node, instruction index ), so the walk
+// has to repeat itself after at most num_nodes * num_instructions steps.
+#[derive( Debug, PartialEq )]
+struct GhostCycle< 'a >
+{
+    start      : &'a str,
+    prefix_hits: Vec< usize >, // steps before the cycle at which the ghost is on a goal node
+    cycle_start: usize,        // first step inside the cycle
+    cycle_len  : usize,
+    cycle_hits : Vec< usize >, // steps inside the first pass of the cycle at which the ghost is on a goal node
+}
+
+impl GhostCycle< '_ >
+{
+    fn first_arrival( &self ) -> Option< usize >
+    {
+        self.prefix_hits.first().or( self.cycle_hits.first() ).copied()
+    }
+
+    fn is_goal_at( &self, step: usize ) -> bool
+    {
+        if step < self.cycle_start
+        {
+            return self.prefix_hits.contains( &step );
+        }
+
+        let first_pass = self.cycle_start + ( step - self.cycle_start ) % self.cycle_len;
+
+        self.cycle_hits.contains( &first_pass )
+    }
+}
+
+// Walks the passes first, jumping a whole pass at a time, to find where the passes start repeating.
+// Only then it walks step by step, up to the end of the first pass through the cycle.
+fn analyse_ghost< 'a >( graph: &Graph< 'a >, start: usize, is_goal: &[ bool ] ) -> GhostCycle< 'a >
+{
+    let num_instructions = graph.instructions.len();
+
+    let mut first_seen_at_pass = vec![ usize::MAX; graph.num_nodes() ];
+
+    let mut node       = start;
+    let mut num_passes = 0usize;
+
+    while first_seen_at_pass[ node ] == usize::MAX
+    {
+        first_seen_at_pass[ node ] = num_passes;
+
+        node        = graph.full_pass[ node ];
+        num_passes += 1;
+    }
+
+    let first_cycle_pass = first_seen_at_pass[ node ];
+    let cycle_len        = ( num_passes - first_cycle_pass ) * num_instructions;
+
+    let mut nodes = Vec::with_capacity( num_passes * num_instructions + 1 );
+    let mut node  = start;
+
+    for step in 0 .. num_passes * num_instructions
+    {
+        nodes.push( node );
+        node = graph.step( node, graph.instructions[ step % num_instructions ] );
+    }
+    nodes.push( node );
+
+    // the states repeat exactly every cycle_len steps, but the cycle may start up to a pass before
+    // the first repeated pass - cycle_len is a multiple of the number of instructions, so comparing
+    // nodes is enough to compare ( node, instruction index ) states
+    let first_candidate = first_cycle_pass.saturating_sub( 1 ) * num_instructions;
+
+    let cycle_start = ( first_candidate ..= first_cycle_pass * num_instructions )
+        .find( | step | nodes[ *step ] == nodes[ *step + cycle_len ] )
+        .unwrap();
+
+    let ( prefix_hits, cycle_hits ) = ( 0 .. cycle_start + cycle_len ).filter( | step | is_goal[ nodes[ *step ] ] ).partition( | step | *step < cycle_start );
+
+    GhostCycle { start: graph.names[ start ], prefix_hits, cycle_start, cycle_len, cycle_hits }
+}
+
+// Every ghost is on a goal exactly at the multiples of its cycle length - then, and only then,
+// the least common multiple of the cycle lengths is the answer.
+fn lcm_applies( ghosts: &[ GhostCycle ] ) -> bool
+{
+    ghosts.iter().all( | ghost | ghost.prefix_hits.is_empty() && ghost.cycle_hits == [ ghost.cycle_len ] )
+}
+
+// First step at which all the ghosts are on a goal node at the same time. Running out of u128 ( or of
+// i128 for the combined moduli ) is reported as an overflow, not as the ghosts never meeting.
+fn simultaneous_arrival( ghosts: &[ GhostCycle ] ) -> Result< u128, CongruenceError >
+{
+    if ghosts.is_empty()
+    {
+        return Err( CongruenceError::NoSolution );
+    }
+
+    if lcm_applies( ghosts )
+    {
+        return checked_lcm_multiple( &ghosts.iter().map( | ghost | ghost.cycle_len ).collect::< Vec< _ > >() ).ok_or( CongruenceError::Overflow );
+    }
+
+    // until the last ghost enters its cycle, steps are checked one by one
+    let window = ghosts.iter().map( | ghost | ghost.cycle_start ).max().unwrap();
 
-    for node in current_nodes
+    if let Some( step ) = ( 0 .. window ).find( | step | ghosts.iter().all( | ghost | ghost.is_goal_at( *step ) ) )
     {
-        let mut num_steps = 0usize;
-        let mut char_iter = instructions.clone().cycle();
+        return Ok( step as u128 );
+    }
 
-        let mut cur_node = node;
+    // Past the window every ghost is inside its cycle, so its goal hits are congruences modulo the cycle
+    // length. Ghosts are combined one at a time, keeping only the distinct combined congruences.
+    let mut combined: BTreeSet< ( i128, i128 ) > = BTreeSet::from( [ ( 0, 1 ) ] );
 
-        while !cur_node.ends_with( 'Z' )
+    for ghost in ghosts
+    {
+        // on a goal at every step of its cycle, never rules out a step
+        if ghost.cycle_hits.len() == ghost.cycle_len
         {
-            num_steps += 1;
-            let instruction = char_iter.next().unwrap();
+            continue;
+        }
 
-            if instruction == 'L' { cur_node = &map[ cur_node ].left; }
-            else                  { cur_node = &map[ cur_node ].right; }
+        let mut next: BTreeSet< ( i128, i128 ) > = BTreeSet::new();
+
+        for ( residue, modulus ) in &combined
+        {
+            for hit in &ghost.cycle_hits
+            {
+                match Congruence::new( *residue, *modulus ).combine( &Congruence::new( *hit as i128, ghost.cycle_len as i128 ) )
+                {
+                    Ok( congruence )                   => { next.insert( ( congruence.residue, congruence.modulus ) ); },
+                    Err( CongruenceError::NoSolution ) => {},
+                    Err( CongruenceError::Overflow   ) => return Err( CongruenceError::Overflow ),
+                }
+            }
         }
 
-        individual_num_steps.push( num_steps );
+        combined = next;
     }
 
-    least_common_multiple( &individual_num_steps )
+    // earliest step of every combined congruence that is not before the window
+    let steps = combined.iter().map
+    (
+        | ( residue, modulus ) |
+        {
+            let ( residue, modulus, window ) = ( *residue as u128, *modulus as u128, window as u128 );
+
+            if residue >= window
+            {
+                return Ok( residue );
+            }
+
+            ( window - residue ).div_ceil( modulus ).checked_mul( modulus )
+                .and_then( | step | residue.checked_add( step ) )
+                .ok_or( CongruenceError::Overflow )
+        }
+    ).collect::< Result< Vec< u128 >, CongruenceError > >()?;
+
+    steps.into_iter().min().ok_or( CongruenceError::NoSolution )
+}
+
+// one ghost for every node matching the start pattern, ordered by name
+fn ghosts< 'a >( graph: &Graph< 'a >, start_pattern: &str, goal_pattern: &str ) -> Vec< GhostCycle< 'a > >
+{
+    let is_start = graph.matching( start_pattern );
+    let is_goal  = graph.matching( goal_pattern );
+
+    ( 0 .. graph.num_nodes() ).filter( | node | is_start[ *node ] ).map( | start | analyse_ghost( graph, start, &is_goal ) ).collect()
 }
 
-fn least_common_multiple( nums: &[ usize ] ) -> usize
+
+#[cfg(test)]
+const TEST_GHOSTS: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+#[test]
+fn test_ghost_cycles()
+{
+    let network = parse_network( TEST_GHOSTS ).unwrap();
+    let ghosts  = ghosts( &Graph::new( &network ), "*A", "*Z" );
+
+    assert_eq!( ghosts[ 0 ], GhostCycle { start: "11A", prefix_hits: vec![], cycle_start: 1, cycle_len: 2, cycle_hits: vec![ 2 ] } );
+    assert_eq!( ghosts[ 1 ], GhostCycle { start: "22A", prefix_hits: vec![], cycle_start: 1, cycle_len: 6, cycle_hits: vec![ 3, 6 ] } );
+
+    assert!( !lcm_applies( &ghosts ) );
+    assert_eq!( simultaneous_arrival( &ghosts ), Ok( 6 ) );
+
+    // the first ghost alone fits the shortcut
+    assert!( lcm_applies( &ghosts[ .. 1 ] ) );
+    assert_eq!( simultaneous_arrival( &ghosts[ .. 1 ] ), Ok( 2 ) );
+}
+
+#[test]
+fn test_arrival_without_lcm()
 {
-    let mut result = 1usize;
+    // goals at 3, 7 and 5, 8, 11, ... - the shortcut would give lcm( 4, 3 ) = 12, which is never a common arrival
+    let first  = GhostCycle { start: "1A", prefix_hits: vec![], cycle_start: 0, cycle_len: 4, cycle_hits: vec![ 3 ] };
+    let second = GhostCycle { start: "2A", prefix_hits: vec![], cycle_start: 2, cycle_len: 3, cycle_hits: vec![ 2 ] };
+
+    let ghosts = [ first, second ];
+    assert!( !lcm_applies( &ghosts ) );
+    assert_eq!( simultaneous_arrival( &ghosts ), Ok( 11 ) );
+
+    // an arrival before the cycle of the second ghost starts
+    let third  = GhostCycle { start: "3A", prefix_hits: vec![ 1 ], cycle_start: 5, cycle_len: 2, cycle_hits: vec![ 6 ] };
+    let fourth = GhostCycle { start: "4A", prefix_hits: vec![], cycle_start: 0, cycle_len: 1, cycle_hits: vec![ 0 ] };
+
+    assert_eq!( simultaneous_arrival( &[ third, fourth ] ), Ok( 1 ) );
+
+    // odd and even steps never meet
+    let odd  = GhostCycle { start: "5A", prefix_hits: vec![], cycle_start: 0, cycle_len: 2, cycle_hits: vec![ 1 ] };
+    let even = GhostCycle { start: "6A", prefix_hits: vec![], cycle_start: 0, cycle_len: 2, cycle_hits: vec![ 0 ] };
+
+    assert_eq!( simultaneous_arrival( &[ odd, even ] ), Err( CongruenceError::NoSolution ) );
+
+    // rings made only of goal nodes - every combination of hits would be 23 * 29 * 31 * 37 * 41 systems
+    let rings: Vec< _ > = [ 23, 29, 31, 37, 41 ].iter().map
+    (
+        | len | GhostCycle { start: "A", prefix_hits: vec![], cycle_start: 1, cycle_len: *len, cycle_hits: ( 1 ..= *len ).collect() }
+    ).collect();
 
-    for num in nums
+    assert_eq!( simultaneous_arrival( &rings ), Ok( 1 ) );
+
+    // two goals on each ring, the arrival is long after the steps that are scanned one by one
+    let rings: Vec< _ > = [ 23, 29, 31, 37, 41 ].iter().enumerate().map
+    (
+        | ( index, len ) |
+        {
+            let mut cycle_hits = vec![ index + 1, 3 * index + 2 ];
+            cycle_hits.sort();
+            GhostCycle { start: "A", prefix_hits: vec![], cycle_start: 0, cycle_len: *len, cycle_hits }
+        }
+    ).collect();
+
+    assert_eq!( simultaneous_arrival( &rings ), Ok( 596851 ) );
+
+    // the least common multiple of the cycle lengths does not fit in u128
+    let lengths = [ usize::MAX, usize::MAX - 1, usize::MAX - 2 ];
+    let ghosts: Vec< _ > = lengths.iter().map
+    (
+        | len | GhostCycle { start: "A", prefix_hits: vec![], cycle_start: 0, cycle_len: *len, cycle_hits: vec![ *len ] }
+    ).collect();
+
+    assert!( lcm_applies( &ghosts ) );
+    assert_eq!( simultaneous_arrival( &ghosts ), Err( CongruenceError::Overflow ) );
+
+    // without the shortcut, the combined modulus of these coprime cycles does not fit into i128
+    let lengths = [ ( 1 << 43 ) - 1, ( 1 << 43 ) + 1, ( 1 << 43 ) + 3 ];
+    let ghosts: Vec< _ > = lengths.iter().map
+    (
+        | len | GhostCycle { start: "A", prefix_hits: vec![], cycle_start: 0, cycle_len: *len, cycle_hits: vec![ 1 ] }
+    ).collect();
+
+    assert_eq!( simultaneous_arrival( &ghosts[ .. 2 ] ), Ok( 1 ) );
+    assert_eq!( simultaneous_arrival( &ghosts ), Err( CongruenceError::Overflow ) );
+}
+
+#[test]
+fn test_patterns()
+{
+    assert!(  matches_pattern( "*A" , "11A" ) );
+    assert!(  matches_pattern( "*A" , "A"   ) );
+    assert!(  matches_pattern( "1?Z", "11Z" ) );
+    assert!( !matches_pattern( "1?Z", "1Z"  ) );
+    assert!(  matches_pattern( "AAA", "AAA" ) );
+    assert!( !matches_pattern( "AAA", "AAB" ) );
+    assert!(  matches_pattern( "*"  , ""    ) );
+
+    let network = parse_network( "LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)" ).unwrap();
+    let graph   = Graph::new( &network );
+
+    let from_aaa = ghosts( &graph, "AAA", "ZZZ" );
+    assert_eq!( from_aaa.len(), 1 );
+    assert_eq!( from_aaa[ 0 ].first_arrival(), Some( 6 ) );
+
+    // ZZZ never leaves itself, so BBB is never reached again from it
+    assert_eq!( ghosts( &graph, "ZZZ", "BBB" )[ 0 ].first_arrival(), None );
+    assert!( ghosts( &graph, "X*", "ZZZ" ).is_empty() );
+}
+
+#[test]
+fn test_graph()
+{
+    let network = parse_network( TEST_GHOSTS ).unwrap();
+    let graph   = Graph::new( &network );
+
+    assert_eq!( graph.names, vec![ "11A", "11B", "11Z", "22A", "22B", "22C", "22Z", "XXX" ] );
+    assert_eq!( ( graph.left[ 0 ], graph.right[ 0 ] ), ( 1, 7 ) );
+
+    // LR from 11A is 11A -> 11B -> 11Z, from 22A it is 22A -> 22B -> 22C
+    assert_eq!( graph.full_pass[ 0 ], 2 );
+    assert_eq!( graph.full_pass[ 3 ], 5 );
+
+    // analysing by whole passes has to agree with a plain step by step walk
+    let is_goal = graph.matching( "*Z" );
+
+    for start in 0 .. graph.num_nodes()
     {
-        result = lcm( result, *num );
+        let ghost = analyse_ghost( &graph, start, &is_goal );
+
+        let mut node = start;
+        for step in 0 .. 50
+        {
+            assert_eq!( ghost.is_goal_at( step ), is_goal[ node ], "start {}, step {}", graph.names[ start ], step );
+            node = graph.step( node, graph.instructions[ step % 2 ] );
+        }
     }
+}
 
-    result
+#[test]
+fn test_reachability_and_dot()
+{
+    let network = parse_network( TEST_GHOSTS ).unwrap();
+    let graph   = Graph::new( &network );
+
+    let ( is_start, is_goal ) = ( graph.matching( "*A" ), graph.matching( "*Z" ) );
+
+    let ( summaries, unreachable ) = reachability( &graph, &is_start, &is_goal );
+
+    // 11A can reach XXX through its right edge, but the instructions never take it there
+    assert_eq!( ( summaries[ 0 ].start, summaries[ 0 ].num_reachable, summaries[ 0 ].num_visited ), ( "11A", 4, 3 ) );
+    assert_eq!( summaries[ 0 ].goals, vec![ "11Z" ] );
+    assert_eq!( ( summaries[ 1 ].num_reachable, summaries[ 1 ].visited_goals.clone() ), ( 5, vec![ "22Z" ] ) );
+    assert!( unreachable.is_empty() );
+
+    let ( summaries, unreachable ) = reachability( &graph, &graph.matching( "22?" ), &is_goal );
+    assert_eq!( summaries.len(), 4 );
+    assert_eq!( unreachable, vec![ "11A", "11B", "11Z" ] );
+
+    let dot = export_dot( &graph, &is_start, &is_goal );
+    assert!( dot.contains( "\"11A\" [style=\"filled\", fillcolor=palegreen];" ) );
+    assert!( dot.contains( "\"22Z\" [style=\"filled\", fillcolor=lightcoral];" ) );
+    assert!( dot.contains( "\"22B\" -> \"22C\" [label=\"L/R\"];" ) );
+    assert!( dot.contains( "\"11B\" -> \"11Z\" [label=\"R\"];" ) );
+    assert!( dot.contains( "    \"XXX\";\n" ) );
+
+    // starting from the 22 nodes only, the 11 ring is never reached, goal or not
+    let dot = export_dot( &graph, &graph.matching( "22?" ), &is_goal );
+    assert!( dot.contains( "\"11Z\" [style=\"filled,dashed\", fillcolor=lightcoral];" ) );
+    assert!( dot.contains( "\"11B\" [style=\"dashed\"];" ) );
+    assert_eq!( dot.matches( "style=" ).count(), 7 );
+
+    // names are taken verbatim from the input, quotes and backslashes must not end the DOT strings
+    let network = parse_network( "L\n\nA\"1 = (B\\2, B\\2)\nB\\2 = (A\"1, A\"1)" ).unwrap();
+    let graph   = Graph::new( &network );
+
+    let dot = export_dot( &graph, &graph.matching( "A*" ), &graph.matching( "B*" ) );
+    assert!( dot.contains( "    \"A\\\"1\" [style=\"filled\", fillcolor=palegreen];\n" ) );
+    assert!( dot.contains( "    \"A\\\"1\" -> \"B\\\\2\" [label=\"L/R\"];\n" ) );
 }
 
+#[test]
+fn test_network_errors()
+{
+    assert_eq!( parse_network( "LXR\n\nAAA = (AAA, AAA)" ).err(), Some( NetworkError::UnknownInstruction { position: 1, instruction: 'X' } ) );
+    assert_eq!( parse_network( "LR\n\nAAA = (AAA, BBB)" ).err(), Some( NetworkError::MissingNode { line: 3, node: "BBB".to_string() } ) );
+    assert_eq!( parse_network( "LR\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)" ).err(), Some( NetworkError::DuplicateNode { line: 4, node: "AAA".to_string() } ) );
+    assert_eq!( parse_network( "LR\n\nAAA = AAA, AAA" ).err(), Some( NetworkError::Malformed { line: 3 } ) );
+    assert_eq!( parse_network( "" ).err(), Some( NetworkError::NoInstructions ) );
+}
 
 fn main()
 {
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut lines = input.lines();
+    let network = parse_network( &input ).unwrap_or_else( | error | panic!( "Invalid network: {}", error ) );
+    let graph   = Graph::new( &network );
 
-    let instructions = lines.next().unwrap();
+    let mut part_01 = ( "AAA".to_string(), "ZZZ".to_string() );
+    let mut part_02 = ( "*A".to_string(), "*Z".to_string() );
 
-    lines.next();
+    let mut dot_path: Option< String > = None;
+    let mut show_reachability = false;
 
-    let map = parse_map( lines ).unwrap();
+    // optional arguments: --part-01=<start pattern>:<goal pattern> and the same for --part-02,
+    // where '?' in a pattern matches any single character and '*' any number of them,
+    // --dot=<path> and --reachability, both using the part 02 patterns
+    for argument in std::env::args().skip( 2 )
+    {
+        if let Some( path ) = argument.strip_prefix( "--dot=" )
+        {
+            dot_path = Some( path.to_string() );
+            continue;
+        }
+        if argument == "--reachability"
+        {
+            show_reachability = true;
+            continue;
+        }
 
-    let part_01_result = part_01_follow_instructions( instructions.chars(), &map );
+        let ( part, patterns ) = if let Some( patterns ) = argument.strip_prefix( "--part-01=" )
+        {
+            ( &mut part_01, patterns )
+        }
+        else if let Some( patterns ) = argument.strip_prefix( "--part-02=" )
+        {
+            ( &mut part_02, patterns )
+        }
+        else
+        {
+            panic!( "Unknown argument: {}", argument );
+        };
 
-    println!( "Part 01 solution: {}", part_01_result );
+        let ( start, goal ) = patterns.split_once( ':' ).expect( "Expected <start pattern>:<goal pattern>" );
 
-    let part_02_result = part_02_follow_instructions( instructions.chars(), &map );
+        *part = ( start.to_string(), goal.to_string() );
+    }
 
-    println!( "Part 02 solution: {}", part_02_result );
+    let ( is_start, is_goal ) = ( graph.matching( &part_02.0 ), graph.matching( &part_02.1 ) );
 
+    if let Some( path ) = dot_path
+    {
+        std::fs::write( path, export_dot( &graph, &is_start, &is_goal ) ).expect( "Failed to write DOT file" );
+    }
+
+    if show_reachability
+    {
+        let ( summaries, unreachable ) = reachability( &graph, &is_start, &is_goal );
+
+        for summary in &summaries
+        {
+            println!
+            (
+                "From {}: {} nodes reachable, {} visited, goals reachable {:?}, goals visited {:?}",
+                summary.start, summary.num_reachable, summary.num_visited, summary.goals, summary.visited_goals
+            );
+        }
+
+        println!( "Unreachable from any start: {} nodes {:?}", unreachable.len(), unreachable );
+    }
+
+    // every start walks on its own
+    let walkers = ghosts( &graph, &part_01.0, &part_01.1 );
+
+    match &walkers[ .. ]
+    {
+        []         => println!( "Part 01: no node matches {}", part_01.0 ),
+        [ walker ] => match walker.first_arrival()
+        {
+            Some( steps ) => println!( "Part 01 solution: {}", steps ),
+            None          => println!( "Part 01: {} never reaches {}", walker.start, part_01.1 ),
+        },
+        _          => for walker in &walkers
+        {
+            match walker.first_arrival()
+            {
+                Some( steps ) => println!( "Part 01 solution from {}: {}", walker.start, steps ),
+                None          => println!( "Part 01: {} never reaches {}", walker.start, part_01.1 ),
+            }
+        },
+    }
+
+    let ghosts = ghosts( &graph, &part_02.0, &part_02.1 );
+
+    for ghost in &ghosts
+    {
+        println!
+        (
+            "Ghost {}: goals at {:?} before the cycle, cycle of {} steps from step {}, goals at {:?}",
+            ghost.start, ghost.prefix_hits, ghost.cycle_len, ghost.cycle_start, ghost.cycle_hits
+        );
+    }
+
+    if lcm_applies( &ghosts )
+    {
+        println!( "Every ghost reaches its goal exactly at multiples of its cycle length" );
+    }
+
+    match simultaneous_arrival( &ghosts )
+    {
+        Ok( steps )                        => println!( "Part 02 solution: {}", steps ),
+        Err( CongruenceError::NoSolution ) => println!( "Part 02: ghosts never arrive at the same time" ),
+        Err( CongruenceError::Overflow   ) => println!( "Part 02: the first simultaneous arrival is too far away to compute" ),
+    }
 }
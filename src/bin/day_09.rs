@@ -1,55 +1,120 @@
-fn extrapolate( numbers: &[ i64 ] ) -> ( i64, i64 )
+use utils::input::integers;
+use utils::sequence::{fit_polynomial, SequenceError};
+
+#[derive( Debug, PartialEq )]
+struct LineReport
 {
-    let mut diffs: Vec< Vec< i64 > > = Vec::new();
+    degree  : usize,
+    backward: Vec< i128 >, // nearest value first
+    forward : Vec< i128 >,
+}
 
-    {
-        let mut prev = numbers;
+fn report_line( numbers: &[ i64 ], steps: usize ) -> Result< LineReport, SequenceError >
+{
+    let fit = fit_polynomial( numbers, None )?;
 
-        while !prev.iter().all( |x| { *x == 0 } )
+    Ok
+    (
+        LineReport
         {
-            let diff = prev.windows( 2 ).map ( | x | { x[ 1 ] - x[ 0 ] }).collect();
-
-            diffs.push( diff );
-            prev = diffs.last().unwrap();
+            degree  : fit.degree(),
+            backward: fit.extrapolate_backward( steps )?,
+            forward : fit.extrapolate_forward ( steps )?,
         }
-    }
-
-    let mut added_number_begin = 0i64;
-    let mut added_number_end   = 0i64;
+    )
+}
 
-    for diff in diffs.iter().rev()
+fn describe_error( error: &SequenceError ) -> String
+{
+    match error
     {
-        added_number_end = added_number_end + diff.last().unwrap();
-        added_number_begin = diff.first().unwrap() - added_number_begin;
+        SequenceError::NotPolynomial { checked_degree } =>
+            format!( "differences never became constant, checked up to degree {}", checked_degree ),
+        SequenceError::Overflow =>
+            "extrapolation overflows i128".to_string(),
     }
+}
 
-    added_number_end = added_number_end + numbers.last().unwrap();
-    added_number_begin = numbers.first().unwrap() - added_number_begin;
+#[test]
+fn test_line_reports()
+{
+    assert_eq!
+    (
+        report_line( &[ 0, 3, 6, 9, 12, 15 ], 2 ),
+        Ok( LineReport { degree: 1, backward: vec![ -3, -6 ], forward: vec![ 18, 21 ] } )
+    );
 
-    ( added_number_begin, added_number_end )
-}
+    let report = report_line( &[ 10, 13, 16, 21, 30, 45 ], 1 ).unwrap();
+    assert_eq!( ( report.degree, report.backward[ 0 ], report.forward[ 0 ] ), ( 3, 5, 68 ) );
 
+    // the last difference row has a single sample, so it cannot be confirmed to be constant
+    assert_eq!( report_line( &[ 1, 2, 4, 8 ], 1 ), Err( SequenceError::NotPolynomial { checked_degree: 2 } ) );
+
+    // binomial( n, 20 ) fits in i64 for the samples, but grows past i128 a thousand steps later
+    let samples: Vec< i64 > = ( 0 .. 22 ).map( | n | match n { 20 => 1, 21 => 21, _ => 0 } ).collect();
+
+    assert_eq!( report_line( &samples, 1 ).map( | r | r.degree ), Ok( 20 ) );
+    assert_eq!( report_line( &samples, 1000 ), Err( SequenceError::Overflow ) );
+}
 
 fn main()
 {
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut part_01_solution = 0i64;
-    let mut part_02_solution = 0i64;
+    let mut steps       = 1usize;
+    let mut show_report = false;
 
-    for line in input.lines()
+    // optional arguments: --steps=<number of values to extrapolate each way> and --report
+    for argument in std::env::args().skip( 2 )
     {
-        let numbers: Vec< _ > = line.split_whitespace().map( str::parse::< i64 > ).map( Result::unwrap ).collect();
+        if let Some( value ) = argument.strip_prefix( "--steps=" )
+        {
+            steps = value.parse().expect( "Invalid number of steps" );
+        }
+        else if argument == "--report"
+        {
+            show_report = true;
+        }
+        else
+        {
+            panic!( "Unknown argument: {}", argument );
+        }
+    }
 
-        let extrapolation = extrapolate( &numbers );
+    let mut part_01_solution: Option< i128 > = Some( 0 );
+    let mut part_02_solution: Option< i128 > = Some( 0 );
 
-        println!( "{} {}", extrapolation.0, extrapolation.1 );
+    for ( index, line ) in input.lines().enumerate()
+    {
+        let numbers: Vec< i64 > = integers( line ).unwrap_or_else( | _ | panic!( "line {}: invalid number", index + 1 ) );
+
+        match report_line( &numbers, steps )
+        {
+            Ok( report ) =>
+            {
+                if show_report
+                {
+                    println!( "Line {}: degree {}, before {:?}, after {:?}", index + 1, report.degree, report.backward, report.forward );
+                }
 
-        part_01_solution += extrapolation.1;
-        part_02_solution += extrapolation.0;
+                if let ( Some( before ), Some( after ) ) = ( report.backward.first(), report.forward.first() )
+                {
+                    part_01_solution = part_01_solution.and_then( | sum | sum.checked_add( *after  ) );
+                    part_02_solution = part_02_solution.and_then( | sum | sum.checked_add( *before ) );
+                }
+            },
+            Err( error ) => println!( "Warning: line {} skipped, {}", index + 1, describe_error( &error ) ),
+        }
     }
 
-    println!( "Part 01 solution: {}", part_01_solution );
-    println!( "Part 02 solution: {}", part_02_solution );
+    match ( part_01_solution, part_02_solution )
+    {
+        ( Some( part_01 ), Some( part_02 ) ) =>
+        {
+            println!( "Part 01 solution: {}", part_01 );
+            println!( "Part 02 solution: {}", part_02 );
+        },
+        _ => println!( "Sum of the extrapolated values overflows i128" ),
+    }
 }
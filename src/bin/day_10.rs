@@ -1,4 +1,6 @@
-#[ derive( Copy, Clone ) ]
+use std::fmt;
+
+#[ derive( Copy, Clone, PartialEq, Debug ) ]
 struct Pos( i64, i64 );
 
 struct Map
@@ -18,13 +20,77 @@ enum Direction
     West,
 }
 
+const DIRECTIONS: [ Direction; 4 ] = [ Direction::North, Direction::East, Direction::South, Direction::West ];
+
+const PIPES: [ char; 6 ] = [ '|', '-', 'L', 'J', '7', 'F' ];
+
+impl Direction
+{
+    fn opposite( self ) -> Direction
+    {
+        match self
+        {
+            Direction::North => Direction::South,
+            Direction::East  => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West  => Direction::East,
+        }
+    }
+
+    fn step( self, pos: Pos ) -> Pos
+    {
+        match self
+        {
+            Direction::North => Pos( pos.0 - 1, pos.1     ),
+            Direction::East  => Pos( pos.0    , pos.1 + 1 ),
+            Direction::South => Pos( pos.0 + 1, pos.1     ),
+            Direction::West  => Pos( pos.0    , pos.1 - 1 ),
+        }
+    }
+}
+
+// whether the tile has an opening towards the given direction
+fn connects( tile: char, direction: Direction ) -> bool
+{
+    matches!
+    (
+        ( tile, direction ),
+        ( '|', Direction::North | Direction::South ) |
+        ( '-', Direction::East  | Direction::West  ) |
+        ( 'L', Direction::North | Direction::East  ) |
+        ( 'J', Direction::North | Direction::West  ) |
+        ( '7', Direction::South | Direction::West  ) |
+        ( 'F', Direction::South | Direction::East  )
+    )
+}
+
+#[derive( Debug, PartialEq )]
+enum MapError
+{
+    NoStart,
+    StartConnections { count: usize },
+}
+
+impl fmt::Display for MapError
+{
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {
+        match self
+        {
+            MapError::NoStart                   => write!( f, "no start tile" ),
+            MapError::StartConnections{ count } => write!( f, "start connects to {} neighbours, expected 2", count ),
+        }
+    }
+}
+
 impl Map
 {
-    fn new( input: String ) -> Map
+    // the tile under 'S' is deduced from its neighbours and put into the layout
+    fn new( input: String ) -> Result< Map, MapError >
     {
         let mut layout: Vec< char > = Vec::new();
         let mut width               = 0;
-        let mut start_pos           = Pos( 0, 0 );
+        let mut start_pos           = None;
         let     height              = input.lines().count();
 
         for ( line_num, line ) in input.lines().enumerate()
@@ -33,132 +99,84 @@ impl Map
 
             if let Some( x ) =  line.find( 'S' )
             {
-                start_pos = Pos( line_num as i64, x as i64 );
+                start_pos = Some( Pos( line_num as i64, x as i64 ) );
             }
             layout.extend( line.chars() );
         }
 
-        Map { layout, width, height, start_pos }
+        let mut map = Map { layout, width, height, start_pos: start_pos.ok_or( MapError::NoStart )? };
+
+        let start_tile = map.start_tile()?;
+        let start_index = map.index( map.start_pos ).unwrap();
+
+        map.layout[ start_index ] = start_tile;
+
+        Ok( map )
     }
 
-    fn at_pos( &self, pos: Pos ) -> Option< char >
+    fn index( &self, pos: Pos ) -> Option< usize >
     {
-        let lin_pos = pos.0 * self.width as i64 + pos.1;
+        if pos.0 < 0 || pos.1 < 0 || pos.0 >= self.height as i64 || pos.1 >= self.width as i64
+        {
+            return None;
+        }
 
-        self.layout.get( lin_pos as usize ).copied()
+        Some( pos.0 as usize * self.width + pos.1 as usize )
     }
 
-    fn find_connection( &self, pos: Pos, start_direction: Direction ) -> ( Pos, Direction )
+    fn at_pos( &self, pos: Pos ) -> Option< char >
     {
-        let try_north = || -> Option< ( Pos, Direction ) >
-        {
-            let candidate = Pos( pos.0 - 1, pos.1 );
-            if let Some( x ) = self.at_pos( candidate )
-            {
-                if x == '|' || x == '7' || x == 'F' || x == 'S'
-                {
-                    return Some( ( candidate, Direction::North ) );
-                }
-            }
-            None
-        };
+        self.index( pos ).and_then( | index | self.layout.get( index ).copied() )
+    }
 
-        let try_east = || -> Option< ( Pos, Direction ) >
-        {
-            let candidate = Pos( pos.0, pos.1 + 1 );
-            if let Some( x ) = self.at_pos( candidate )
-            {
-                if x == '-' || x == '7' || x == 'J' || x == 'S'
-                {
-                    return Some( ( candidate, Direction::East ) );
-                }
-            }
-            None
-        };
+    // the only pipe connecting exactly the neighbours that connect back to the start
+    fn start_tile( &self ) -> Result< char, MapError >
+    {
+        let connected: Vec< Direction > = DIRECTIONS.iter().copied().filter
+        (
+            | direction | self.at_pos( direction.step( self.start_pos ) ).is_some_and( | tile | connects( tile, direction.opposite() ) )
+        ).collect();
 
-        let try_south = || -> Option< ( Pos, Direction ) >
+        if connected.len() != 2
         {
-            let candidate = Pos( pos.0 + 1, pos.1 );
-            if let Some( x ) = self.at_pos( candidate )
-            {
-                if x == '|' || x == 'J' || x == 'L' || x == 'S'
-                {
-                    return Some( ( candidate, Direction::South ) );
-                }
-            }
-            None
-        };
+            return Err( MapError::StartConnections { count: connected.len() } );
+        }
 
-        let try_west = || -> Option< ( Pos, Direction ) >
-        {
-            let candidate = Pos( pos.0, pos.1 - 1 );
-            if let Some( x ) = self.at_pos( candidate )
-            {
-                if x == '-' || x == 'F' || x == 'L' || x == 'S'
-                {
-                    return Some( ( candidate, Direction::West ) );
-                }
-            }
-            None
-        };
+        Ok( *PIPES.iter().find( | pipe | connected.iter().all( | direction | connects( **pipe, *direction ) ) ).unwrap() )
+    }
 
-        match self.at_pos( pos ).unwrap()
+    // next position along the pipe, when entering pos heading in start_direction
+    fn find_connection( &self, pos: Pos, start_direction: Direction ) -> ( Pos, Direction )
+    {
+        let tile = self.at_pos( pos ).unwrap();
+
+        let direction = DIRECTIONS.iter().copied()
+            .find( | direction | *direction != start_direction.opposite() && connects( tile, *direction ) )
+            .unwrap_or_else( || panic!( "Unknown marking {} at ({}, {})", tile, pos.0, pos.1 ) );
+
+        let next = direction.step( pos );
+
+        match self.at_pos( next )
         {
-            'S' => { try_north().or( try_east() ).or( try_south() ).or( try_west() ).unwrap() }
-            '|' =>
-            {
-                if start_direction == Direction::South
-                {
-                    // coming from North, so don't go back there
-                    try_south().unwrap()
-                }
-                else
-                {
-                    try_north().unwrap()
-                }
-            },
-            '-' =>
-            {
-                if start_direction == Direction::East { try_east().unwrap() }
-                else                                  { try_west().unwrap() }
-            },
-            'J' =>
-            {
-                if start_direction == Direction::South { try_west ().unwrap() }
-                else                                   { try_north().unwrap() }
-            },
-            'L' =>
-            {
-                if start_direction == Direction::South { try_east ().unwrap() }
-                else                                   { try_north().unwrap() }
-            },
-            'F' =>
-            {
-                if start_direction == Direction::North { try_east ().unwrap() }
-                else                                   { try_south().unwrap() }
-            },
-            '7' =>
-            {
-                if start_direction == Direction::East { try_south().unwrap() }
-                else                                  { try_west ().unwrap() }
-            },
-            _ => { panic!( "Unknown marking!" ) }
+            Some( next_tile ) if connects( next_tile, direction.opposite() ) => ( next, direction ),
+            _ => panic!( "Pipe at ({}, {}) leads nowhere", pos.0, pos.1 ),
         }
     }
 
+    // every loop tile with the direction it is entered and left in, ending with the start
     fn find_loop( &self ) -> Vec< ( Pos, Direction, Direction ) >
     {
-        // let mut lp = vec![ self.start_pos ];
         let mut lp: Vec< ( Pos, Direction, Direction ) > = Vec::new();
 
-        let mut cur_pos = self.find_connection( self.start_pos, Direction::North );
+        let start_tile = self.at_pos( self.start_pos ).unwrap();
 
-        // println!( "Start: ({}, {})", self.start_pos.0, self.start_pos.1 );
+        // pretend to arrive at the start through one of its openings
+        let arrival = DIRECTIONS.iter().copied().find( | direction | connects( start_tile, *direction ) ).unwrap().opposite();
 
-        while self.at_pos( cur_pos.0 ) != Some( 'S' )
-        {
-            // println!( "Gone {:?} to pos: ({}, {}), mark: {}", cur_pos.1, cur_pos.0.0, cur_pos.0.1, self.at_pos( cur_pos.0 ).unwrap() );
+        let mut cur_pos = self.find_connection( self.start_pos, arrival );
 
+        while cur_pos.0 != self.start_pos
+        {
             let new_pos = self.find_connection( cur_pos.0, cur_pos.1 );
 
             lp.push( ( cur_pos.0, cur_pos.1, new_pos.1 ) );
@@ -170,6 +188,64 @@ impl Map
 
         lp
     }
+
+    // scanning each row, crossing a loop tile that opens to the north toggles being inside
+    fn num_tiles_inside( &self, lp: &[ ( Pos, Direction, Direction ) ] ) -> usize
+    {
+        let mut on_loop = vec![ false; self.layout.len() ];
+
+        for ( pos, _, _ ) in lp
+        {
+            on_loop[ self.index( *pos ).unwrap() ] = true;
+        }
+
+        let mut num_tiles_inside = 0usize;
+
+        for row in 0 .. self.height
+        {
+            let mut is_inside = false;
+            for col in 0 .. self.width
+            {
+                let index = row * self.width + col;
+
+                if on_loop[ index ]
+                {
+                    if connects( self.layout[ index ], Direction::North )
+                    {
+                        is_inside = !is_inside;
+                    }
+                }
+                else if is_inside
+                {
+                    num_tiles_inside += 1;
+                }
+            }
+        }
+
+        num_tiles_inside
+    }
+}
+
+#[test]
+fn test_start_tile()
+{
+    let map = Map::new( "7-F7-\n.FJ|7\nSJLL7\n|F--J\nLJ.LJ".to_string() ).unwrap();
+
+    assert_eq!( map.at_pos( map.start_pos ), Some( 'F' ) );
+    assert_eq!( map.find_loop().len() / 2, 8 );
+
+    let map = Map::new( "..........\n.S------7.\n.|F----7|.\n.||....||.\n.||....||.\n.|L-7F-J|.\n.|..||..|.\n.L--JL--J.\n..........".to_string() ).unwrap();
+
+    assert_eq!( map.at_pos( map.start_pos ), Some( 'F' ) );
+    assert_eq!( map.num_tiles_inside( &map.find_loop() ), 4 );
+
+    // three pipes lead into the start, and none at all
+    assert_eq!( Map::new( ".|.\n-S-\n...".to_string() ).err(), Some( MapError::StartConnections { count: 3 } ) );
+    assert_eq!( Map::new( "...\n.S.\n...".to_string() ).err(), Some( MapError::StartConnections { count: 0 } ) );
+    assert_eq!( Map::new( "...\n...".to_string() ).err(), Some( MapError::NoStart ) );
+
+    // a pipe pointing at the start from outside the row does not wrap around
+    assert_eq!( Map::new( "..-\nS-7\n..|".to_string() ).err(), Some( MapError::StartConnections { count: 1 } ) );
 }
 
 fn main()
@@ -177,7 +253,9 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let map = Map::new( input );
+    let map = Map::new( input ).unwrap_or_else( | error | panic!( "Invalid map: {}", error ) );
+
+    println!( "Tile under S: {}", map.at_pos( map.start_pos ).unwrap() );
 
     let lp = map.find_loop();
 
@@ -189,37 +267,5 @@ fn main()
     // Part 02
     //------------------------------------------------------------------------------
 
-    let is_on_loop = | pos: Pos |
-    {
-        lp.iter().find(| x | { x.0.0 == pos.0 && x.0.1 == pos.1 })
-    };
-
-    let mut num_tiles_inside = 0usize;
-
-    for row in 0 .. map.height
-    {
-        let mut is_inside = false;
-        for col in 0 .. map.width
-        {
-            let cur_pos = Pos( row as i64, col as i64 );
-            if let Some( x ) = is_on_loop( cur_pos ) // loop border
-            {
-                // println!( "Pos ({}, {}) is on loop, heading {:?} -> {:?}", x.0.0, x.0.1, x.1, x.2 );
-                // if connected below, then toggle
-                if x.1 == Direction::North || x.2 == Direction::South
-                {
-                    is_inside = !is_inside;
-                    // println!( "toggled" );
-                }
-            }
-            else if is_inside
-            {
-                // println!( "Pos ({}, {}), mark: {} is inside!", cur_pos.0, cur_pos.1, map.at_pos( cur_pos ).unwrap() );
-                num_tiles_inside += 1;
-            }
-        }
-    }
-
-    println!( "Num tiles inside: {}", num_tiles_inside );
+    println!( "Num tiles inside: {}", map.num_tiles_inside( &lp ) );
 }
-
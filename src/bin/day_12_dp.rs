@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use utils::memo::{DenseCache, DenseKey, Memo};
 
 fn parse_line( line: &str ) -> ( String, Vec< usize > )
 {
@@ -22,90 +22,90 @@ struct State
     last_block_size: usize,
 }
 
-type Cache = HashMap< State, usize >;
-
-fn solve( pattern: &str, expected_runchain: &[ usize ], cache: &mut Cache, state: State ) -> usize
+impl DenseKey< 3 > for State
 {
-    if let Some( cached_result ) = cache.get( &state )
+    fn coordinates( &self ) -> [ usize; 3 ]
     {
-        return *cached_result;
-    }
-    // reached end of pattern - check if we have solution
-    if state.char_index == pattern.len()
-    {
-        if state.chain_index == expected_runchain.len() && state.last_block_size == 0
-        {
-            return 1;
-        }
-        else if state.chain_index == expected_runchain.len() - 1 && state.last_block_size == expected_runchain[ state.chain_index ]
-        {
-            return 1;
-        }
-        else
-        {
-            return 0;
-        }
+        [ self.char_index, self.chain_index, self.last_block_size ]
     }
+}
 
-    let mut num_solutions_for_input_state = 0usize;
+type Cache = Memo< State, usize, DenseCache< usize, 3 > >;
 
-    for c in vec![ '.', '#' ]
-    {
-        let p = pattern.chars().nth( state.char_index ).unwrap();
-
-        if p == c || p == '?'
+fn solve( pattern: &str, expected_runchain: &[ usize ], cache: &mut Cache, state: State ) -> usize
+{
+    cache.solve
+    (
+        state,
+        &| solve, state |
         {
-            if c == '.' && state.last_block_size == 0
+            // reached end of pattern - check if we have solution
+            if state.char_index == pattern.len()
             {
-                num_solutions_for_input_state += solve
-                (
-                    &pattern,
-                    &expected_runchain,
-                    cache,
-                    State
-                    {
-                        char_index: state.char_index + 1,
-                        chain_index: state.chain_index,
-                        last_block_size: 0,
-                    }
-                );
+                if state.chain_index == expected_runchain.len() && state.last_block_size == 0
+                {
+                    return 1;
+                }
+                else if state.chain_index == expected_runchain.len() - 1 && state.last_block_size == expected_runchain[ state.chain_index ]
+                {
+                    return 1;
+                }
+                else
+                {
+                    return 0;
+                }
             }
-            else if c == '.' && state.last_block_size > 0 && state.chain_index < expected_runchain.len() && expected_runchain[ state.chain_index ] == state.last_block_size
+
+            let mut num_solutions_for_input_state = 0usize;
+
+            for c in [ '.', '#' ]
             {
-                num_solutions_for_input_state += solve
-                (
-                    &pattern,
-                    &expected_runchain,
-                    cache,
-                    State
+                let p = pattern.chars().nth( state.char_index ).unwrap();
+
+                if p == c || p == '?'
+                {
+                    if c == '.' && state.last_block_size == 0
                     {
-                        char_index: state.char_index + 1,
-                        chain_index: state.chain_index + 1,
-                        last_block_size: 0,
+                        num_solutions_for_input_state += solve
+                        (
+                            State
+                            {
+                                char_index: state.char_index + 1,
+                                chain_index: state.chain_index,
+                                last_block_size: 0,
+                            }
+                        );
                     }
-                );
-            }
-            else if c == '#'
-            {
-                num_solutions_for_input_state += solve
-                (
-                    &pattern,
-                    &expected_runchain,
-                    cache,
-                    State
+                    else if c == '.' && state.last_block_size > 0 && state.chain_index < expected_runchain.len() && expected_runchain[ state.chain_index ] == state.last_block_size
                     {
-                        char_index: state.char_index + 1,
-                        chain_index: state.chain_index,
-                        last_block_size: state.last_block_size + 1,
+                        num_solutions_for_input_state += solve
+                        (
+                            State
+                            {
+                                char_index: state.char_index + 1,
+                                chain_index: state.chain_index + 1,
+                                last_block_size: 0,
+                            }
+                        );
                     }
-                );
+                    else if c == '#'
+                    {
+                        num_solutions_for_input_state += solve
+                        (
+                            State
+                            {
+                                char_index: state.char_index + 1,
+                                chain_index: state.chain_index,
+                                last_block_size: state.last_block_size + 1,
+                            }
+                        );
+                    }
+                }
             }
-        }
-    }
 
-    cache.insert( state, num_solutions_for_input_state );
-
-    num_solutions_for_input_state
+            num_solutions_for_input_state
+        }
+    )
 }
 
 fn solve_line( line: &str, expand: usize ) -> usize
@@ -118,7 +118,8 @@ fn solve_line( line: &str, expand: usize ) -> usize
     }
     let expected_runchain = expected_runchain.repeat( expand );
 
-    let mut cache = Cache::new();
+    // every state component is bounded by the pattern and runchain lengths
+    let mut cache = Cache::with_cache( DenseCache::new( [ pattern.len() + 1, expected_runchain.len() + 1, pattern.len() + 1 ] ) );
 
     solve
     (
@@ -1,3 +1,4 @@
+use utils::input::grid_blocks;
 use utils::matrix::{MatrixTrait, Matrix};
 
 fn find_similar_rows( pattern: &Matrix< u8 > ) -> Matrix< bool >
@@ -168,41 +169,18 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut current_pattern: Matrix< u8 > = Matrix
-    {
-        data: Vec::new(),
-        width: 0,
-        height: 0,
-    };
-
     let mut part_01 = 0usize;
     let mut part_02 = 0usize;
 
-    for line in input.lines()
+    for pattern in grid_blocks( &input )
     {
-        if !line.is_empty()
-        {
-            current_pattern.data.extend( line.as_bytes() );
-            current_pattern.width = line.len();
-            current_pattern.height += 1;
-        }
-        else
-        {
-            // current_pattern.print();
-            part_01 += find_reflection_score            ( &current_pattern );
-            part_02 += find_reflection_score_with_smudge( &current_pattern );
+        // pattern.print();
+        part_01 += find_reflection_score            ( &pattern );
+        part_02 += find_reflection_score_with_smudge( &pattern );
 
-            current_pattern.reset();
-            println!();
-        }
+        println!();
     }
 
-    // last pattern
-    // current_pattern.print();
-    part_01 += find_reflection_score            ( &current_pattern );
-    part_02 += find_reflection_score_with_smudge( &current_pattern );
-    println!();
-
     println!( "Part 01: {}", part_01 );
     println!( "Part 02: {}", part_02 );
 }
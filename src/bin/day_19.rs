@@ -1,5 +1,7 @@
 use std::collections::{ HashMap, VecDeque };
 
+use utils::input::blocks;
+
 #[ derive( Debug ) ]
 struct Part
 {
@@ -299,32 +301,10 @@ fn main()
     let file_path = std::env::args().nth( 1 ).unwrap();
     let input     = std::fs::read_to_string( file_path ).expect( "Failed to read file" );
 
-    let mut workflows: HashMap< &str, Vec< Rule > > = HashMap::new();
-    let mut parts: Vec< Part > = Vec::new();
-
-    let mut parsing_workflows = true;
+    let mut sections = blocks( &input );
 
-    for line in input.lines()
-    {
-        if parsing_workflows
-        {
-            if line.is_empty()
-            {
-                parsing_workflows = false;
-                continue;
-            }
-            else
-            {
-                let ( name, rules ) = parse_workflow( line );
-
-                workflows.insert( name, rules );
-            }
-        }
-        else
-        {
-            parts.push( parse_part( line ) );
-        }
-    }
+    let workflows: HashMap< &str, Vec< Rule > > = sections.next().unwrap().lines().map( parse_workflow ).collect();
+    let parts    : Vec< Part >                  = sections.next().unwrap_or_default().lines().map( parse_part ).collect();
 
     solve_part01( &parts, &workflows );
     solve_part02( &workflows );
@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use crate::matrix::{from_str_input, Matrix};
+
+// Iterator over blank-line separated sections of the input. Every block is a slice of the
+// original input without the surrounding blank lines, so it can be further split with lines().
+pub struct Blocks< 'a >
+{
+    rest      : &'a str,
+    lines_read: usize,
+    block_line: usize, // 1-based line number of the first line of the last returned block
+}
+
+fn split_first_line( text: &str ) -> ( &str, &str )
+{
+    match text.find( '\n' )
+    {
+        Some( pos ) => ( &text[ .. pos ], &text[ pos + 1 .. ] ),
+        None        => ( text, "" ),
+    }
+}
+
+impl< 'a > Iterator for Blocks< 'a >
+{
+    type Item = &'a str;
+
+    fn next( &mut self ) -> Option< &'a str >
+    {
+        // skip blank lines before the block
+        while !self.rest.is_empty()
+        {
+            let ( line, rest ) = split_first_line( self.rest );
+            if !line.trim().is_empty()
+            {
+                break;
+            }
+            self.rest = rest;
+            self.lines_read += 1;
+        }
+
+        if self.rest.is_empty()
+        {
+            return None;
+        }
+
+        self.block_line = self.lines_read + 1;
+
+        let block_start = self.rest;
+        let mut block_len = 0usize;
+
+        while !self.rest.is_empty()
+        {
+            let ( line, rest ) = split_first_line( self.rest );
+            if line.trim().is_empty()
+            {
+                break;
+            }
+            block_len = block_start.len() - self.rest.len() + line.trim_end_matches( '\r' ).len();
+            self.rest = rest;
+            self.lines_read += 1;
+        }
+
+        Some( &block_start[ .. block_len ] )
+    }
+}
+
+pub fn blocks( input: &str ) -> Blocks< '_ >
+{
+    Blocks { rest: input, lines_read: 0, block_line: 0 }
+}
+
+// blocks together with the 1-based line number of their first line, for error reporting
+pub fn numbered_blocks( input: &str ) -> impl Iterator< Item = ( usize, &str ) >
+{
+    let mut blocks = blocks( input );
+
+    std::iter::from_fn( move || blocks.next().map( | block | ( blocks.block_line, block ) ) )
+}
+
+// every blank-line separated block parsed as a grid of bytes
+pub fn grid_blocks( input: &str ) -> impl Iterator< Item = Matrix< u8 > > + '_
+{
+    blocks( input ).map( from_str_input )
+}
+
+// All integers appearing in the line, in order. A '-' or '+' directly in front of a number is
+// treated as its sign, unless it follows another digit (so "3-4" is 3 and 4, not 3 and -4).
+pub fn integers< T >( line: &str ) -> Result< Vec< T >, T::Err >
+where
+    T: FromStr,
+{
+    let bytes = line.as_bytes();
+
+    let mut result = Vec::new();
+    let mut index  = 0usize;
+
+    while index < bytes.len()
+    {
+        if !bytes[ index ].is_ascii_digit()
+        {
+            index += 1;
+            continue;
+        }
+
+        let mut start = index;
+
+        let has_sign      = start > 0 && ( bytes[ start - 1 ] == b'-' || bytes[ start - 1 ] == b'+' );
+        let follows_digit = start > 1 && bytes[ start - 2 ].is_ascii_digit();
+
+        if has_sign && !follows_digit
+        {
+            start -= 1;
+        }
+
+        while index < bytes.len() && bytes[ index ].is_ascii_digit()
+        {
+            index += 1;
+        }
+
+        result.push( line[ start .. index ].parse()? );
+    }
+
+    Ok( result )
+}
+
+// splits "key: values" into trimmed key and values
+pub fn key_value( line: &str ) -> Option< ( &str, &str ) >
+{
+    let ( key, value ) = line.split_once( ':' )?;
+
+    Some( ( key.trim(), value.trim() ) )
+}
+
+#[test]
+fn test_blocks()
+{
+    let input = "a\nb\n\nc\n\n\n  \nd\ne\n";
+    assert_eq!( blocks( input ).collect::< Vec< _ > >(), vec![ "a\nb", "c", "d\ne" ] );
+
+    let input = "\r\nfirst\r\nsecond\r\n\r\nlast";
+    assert_eq!( blocks( input ).collect::< Vec< _ > >(), vec![ "first\r\nsecond", "last" ] );
+
+    assert_eq!( blocks( "" ).count(), 0 );
+    assert_eq!( blocks( "\n\n" ).count(), 0 );
+}
+
+#[test]
+fn test_numbered_blocks()
+{
+    let input = "a\nb\n\nc\n\n\n  \nd\ne\n";
+    assert_eq!( numbered_blocks( input ).collect::< Vec< _ > >(), vec![ ( 1, "a\nb" ), ( 4, "c" ), ( 8, "d\ne" ) ] );
+
+    let input = "\r\nfirst\r\nsecond\r\n\r\nlast";
+    assert_eq!( numbered_blocks( input ).collect::< Vec< _ > >(), vec![ ( 2, "first\r\nsecond" ), ( 5, "last" ) ] );
+}
+
+#[test]
+fn test_grid_blocks()
+{
+    let grids: Vec< _ > = grid_blocks( "#.#\n.#.\n\n##\n##\n##" ).collect();
+
+    assert_eq!( grids.len(), 2 );
+    assert_eq!( ( grids[ 0 ].width, grids[ 0 ].height ), ( 3, 2 ) );
+    assert_eq!( ( grids[ 1 ].width, grids[ 1 ].height ), ( 2, 3 ) );
+    assert_eq!( grids[ 0 ].row( 1 ), b".#." );
+}
+
+#[test]
+fn test_integers()
+{
+    assert_eq!( integers::< i64 >( "seeds: 79 14 55 13" ), Ok( vec![ 79, 14, 55, 13 ] ) );
+    assert_eq!( integers::< i64 >( "x=-3, y=+4 and 10-20" ), Ok( vec![ -3, 4, 10, 20 ] ) );
+    assert_eq!( integers::< i64 >( "-7" ), Ok( vec![ -7 ] ) );
+    assert_eq!( integers::< i64 >( "no numbers" ), Ok( vec![] ) );
+    assert!( integers::< usize >( "1 -2" ).is_err() );
+}
+
+#[test]
+fn test_key_value()
+{
+    assert_eq!( key_value( "Time:      7  15   30" ), Some( ( "Time", "7  15   30" ) ) );
+    assert_eq!( key_value( "no separator" ), None );
+}
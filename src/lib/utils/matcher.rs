@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+// Aho-Corasick automaton matching many patterns in a single pass over the text.
+// Works on bytes - since both patterns and text are valid UTF-8, matches can only ever
+// start and end on character boundaries.
+
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub struct Match
+{
+    pub pattern: usize,
+    pub start  : usize,
+    pub end    : usize, // exclusive, in bytes
+}
+
+impl Match
+{
+    pub fn len( &self ) -> usize
+    {
+        self.end - self.start
+    }
+
+    pub fn is_empty( &self ) -> bool
+    {
+        self.start == self.end
+    }
+}
+
+struct State
+{
+    transitions: [ usize; 256 ],
+    // patterns ending in this state, including those reachable through failure links
+    outputs    : Vec< usize >,
+}
+
+pub struct MultiMatcher
+{
+    states      : Vec< State >,
+    pattern_lens: Vec< usize >,
+}
+
+const ROOT: usize = 0;
+
+impl MultiMatcher
+{
+    pub fn new< S >( patterns: &[ S ] ) -> MultiMatcher
+    where
+        S: AsRef< str >,
+    {
+        // usize::MAX marks a missing edge in the trie, before the automaton is completed
+        let empty_state = || State { transitions: [ usize::MAX; 256 ], outputs: Vec::new() };
+
+        let mut states = vec![ empty_state() ];
+
+        // build trie
+        for ( index, pattern ) in patterns.iter().enumerate()
+        {
+            let mut current = ROOT;
+
+            for byte in pattern.as_ref().bytes()
+            {
+                if states[ current ].transitions[ byte as usize ] == usize::MAX
+                {
+                    states.push( empty_state() );
+                    states[ current ].transitions[ byte as usize ] = states.len() - 1;
+                }
+                current = states[ current ].transitions[ byte as usize ];
+            }
+
+            // empty patterns would match everywhere, ignore them
+            if current != ROOT
+            {
+                states[ current ].outputs.push( index );
+            }
+        }
+
+        // breadth first completion of the automaton - missing edges follow the failure links
+        let mut failure: Vec< usize > = vec![ ROOT; states.len() ];
+        let mut queue: VecDeque< usize > = VecDeque::new();
+
+        for byte in 0 .. 256
+        {
+            let next = states[ ROOT ].transitions[ byte ];
+            if next == usize::MAX
+            {
+                states[ ROOT ].transitions[ byte ] = ROOT;
+            }
+            else
+            {
+                queue.push_back( next );
+            }
+        }
+
+        while let Some( current ) = queue.pop_front()
+        {
+            let inherited = states[ failure[ current ] ].outputs.clone();
+            states[ current ].outputs.extend( inherited );
+
+            for byte in 0 .. 256
+            {
+                let next          = states[ current ].transitions[ byte ];
+                let fallback_next = states[ failure[ current ] ].transitions[ byte ];
+
+                if next == usize::MAX
+                {
+                    states[ current ].transitions[ byte ] = fallback_next;
+                }
+                else
+                {
+                    failure[ next ] = fallback_next;
+                    queue.push_back( next );
+                }
+            }
+        }
+
+        let pattern_lens = patterns.iter().map( | p | p.as_ref().len() ).collect();
+
+        MultiMatcher { states, pattern_lens }
+    }
+
+    pub fn num_patterns( &self ) -> usize
+    {
+        self.pattern_lens.len()
+    }
+
+    // calls the visitor for every ( possibly overlapping ) match, ordered by end position
+    pub fn for_each_match< F >( &self, text: &str, mut visitor: F )
+    where
+        F: FnMut( Match ),
+    {
+        let mut current = ROOT;
+
+        for ( position, byte ) in text.bytes().enumerate()
+        {
+            current = self.states[ current ].transitions[ byte as usize ];
+
+            for pattern in &self.states[ current ].outputs
+            {
+                let end = position + 1;
+                visitor( Match { pattern: *pattern, start: end - self.pattern_lens[ *pattern ], end } );
+            }
+        }
+    }
+
+    pub fn find_all( &self, text: &str ) -> Vec< Match >
+    {
+        let mut matches = Vec::new();
+
+        self.for_each_match( text, | m | matches.push( m ) );
+
+        matches
+    }
+
+    // Leftmost match ( longest one if several start at the same position ) and rightmost match
+    // ( longest one if several end at the same position ), found in a single pass.
+    pub fn first_and_last( &self, text: &str ) -> Option< ( Match, Match ) >
+    {
+        let mut first: Option< Match > = None;
+        let mut last : Option< Match > = None;
+
+        self.for_each_match
+        (
+            text,
+            | m |
+            {
+                let better_first = match first
+                {
+                    None          => true,
+                    Some( first ) => m.start < first.start || ( m.start == first.start && m.len() > first.len() ),
+                };
+                let better_last = match last
+                {
+                    None         => true,
+                    Some( last ) => m.end > last.end || ( m.end == last.end && m.len() > last.len() ),
+                };
+
+                if better_first { first = Some( m ); }
+                if better_last  { last  = Some( m ); }
+            }
+        );
+
+        Some( ( first?, last? ) )
+    }
+}
+
+#[test]
+fn test_find_all()
+{
+    let matcher = MultiMatcher::new( &[ "he", "she", "his", "hers" ] );
+
+    let found: Vec< _ > = matcher.find_all( "ushers" ).iter().map( | m | ( m.pattern, m.start, m.end ) ).collect();
+
+    assert_eq!( found, vec![ ( 1, 1, 4 ), ( 0, 2, 4 ), ( 3, 2, 6 ) ] );
+    assert_eq!( matcher.num_patterns(), 4 );
+}
+
+#[test]
+fn test_first_and_last()
+{
+    let matcher = MultiMatcher::new( &[ "one", "eight", "two", "bc", "abcd" ] );
+
+    let ( first, last ) = matcher.first_and_last( "xoneightwox" ).unwrap();
+    assert_eq!( ( first.pattern, first.start, first.end ), ( 0, 1, 4 ) );
+    assert_eq!( ( last.pattern , last.start , last.end  ), ( 2, 7, 10 ) );
+
+    // a longer match starting earlier wins, even though the shorter one ends first
+    let ( first, last ) = matcher.first_and_last( "abcd" ).unwrap();
+    assert_eq!( first.pattern, 4 );
+    assert_eq!( last.pattern , 4 );
+
+    assert_eq!( matcher.first_and_last( "nothing here" ), None );
+}
+
+#[test]
+fn test_utf8()
+{
+    let matcher = MultiMatcher::new( &[ "četiri", "šest", "č" ] );
+
+    let ( first, last ) = matcher.first_and_last( "ščetiri→šestž" ).unwrap();
+
+    assert_eq!( ( first.pattern, first.start ), ( 0, 2 ) );
+    assert_eq!( ( last.pattern, last.end ), ( 1, "ščetiri→šest".len() ) );
+
+    // multi-byte characters in the text never produce partial matches
+    assert!( MultiMatcher::new( &[ "a" ] ).find_all( "→ŠĆ🦀" ).is_empty() );
+    assert!( MultiMatcher::new( &[ "" ] ).find_all( "abc" ).is_empty() );
+}
@@ -0,0 +1,349 @@
+use std::ops::RangeInclusive;
+
+use num_integer::{gcd, lcm};
+
+pub fn gcd_multiple( nums: &[ usize ] ) -> usize
+{
+    nums.iter().fold( 0usize, | acc, num | gcd( acc, *num ) )
+}
+
+pub fn lcm_multiple( nums: &[ usize ] ) -> usize
+{
+    nums.iter().fold( 1usize, | acc, num | lcm( acc, *num ) )
+}
+
+// least common multiple in u128, None if it does not fit
+pub fn checked_lcm_multiple( nums: &[ usize ] ) -> Option< u128 >
+{
+    nums.iter().try_fold( 1u128, | acc, num | ( acc / gcd( acc, *num as u128 ) ).checked_mul( *num as u128 ) )
+}
+
+// returns ( g, x, y ) such that a * x + b * y = g = gcd( a, b ), with g >= 0
+pub fn extended_gcd( a: i128, b: i128 ) -> ( i128, i128, i128 )
+{
+    let ( mut old_r, mut r ) = ( a, b );
+    let ( mut old_x, mut x ) = ( 1i128, 0i128 );
+    let ( mut old_y, mut y ) = ( 0i128, 1i128 );
+
+    while r != 0
+    {
+        let quotient = old_r / r;
+
+        ( old_r, r ) = ( r, old_r - quotient * r );
+        ( old_x, x ) = ( x, old_x - quotient * x );
+        ( old_y, y ) = ( y, old_y - quotient * y );
+    }
+
+    if old_r < 0
+    {
+        ( -old_r, -old_x, -old_y )
+    }
+    else
+    {
+        ( old_r, old_x, old_y )
+    }
+}
+
+pub fn mod_inverse( a: i128, modulus: i128 ) -> Option< i128 >
+{
+    if modulus <= 0
+    {
+        return None;
+    }
+
+    let ( g, x, _ ) = extended_gcd( a.rem_euclid( modulus ), modulus );
+
+    if g != 1
+    {
+        return None;
+    }
+
+    Some( x.rem_euclid( modulus ) )
+}
+
+// a * b % modulus for 0 <= a, b < modulus, by doubling and adding so that nothing exceeds 2 * modulus
+fn mul_mod( a: i128, b: i128, modulus: i128 ) -> i128
+{
+    let ( mut a, mut b, modulus ) = ( a as u128, b as u128, modulus as u128 );
+    let mut result = 0u128;
+
+    while b > 0
+    {
+        if b & 1 == 1
+        {
+            result = ( result + a ) % modulus;
+        }
+        a   = ( a + a ) % modulus;
+        b >>= 1;
+    }
+
+    result as i128
+}
+
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub enum CongruenceError
+{
+    NoSolution, // the congruences contradict each other
+    Overflow,   // a solution may exist, but the combined modulus or the time does not fit
+}
+
+// x ≡ residue ( mod modulus ), with 0 <= residue < modulus
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub struct Congruence
+{
+    pub residue: i128,
+    pub modulus: i128,
+}
+
+impl Congruence
+{
+    pub fn new( residue: i128, modulus: i128 ) -> Congruence
+    {
+        assert!( modulus > 0, "modulus must be positive" );
+
+        Congruence { residue: residue.rem_euclid( modulus ), modulus }
+    }
+
+    // combines two congruences into one that holds exactly when both of them do
+    pub fn combine( &self, other: &Congruence ) -> Result< Congruence, CongruenceError >
+    {
+        let ( g, p, _ ) = extended_gcd( self.modulus, other.modulus );
+
+        let diff = other.residue - self.residue;
+
+        if diff % g != 0
+        {
+            return Err( CongruenceError::NoSolution );
+        }
+
+        let reduced_modulus = other.modulus / g;
+        let k = mul_mod( ( diff / g ).rem_euclid( reduced_modulus ), p.rem_euclid( reduced_modulus ), reduced_modulus );
+
+        let modulus = ( self.modulus / g ).checked_mul( other.modulus ).ok_or( CongruenceError::Overflow )?;
+        let residue = self.modulus.checked_mul( k )
+            .and_then( | step | self.residue.checked_add( step ) )
+            .ok_or( CongruenceError::Overflow )?;
+
+        Ok( Congruence::new( residue, modulus ) )
+    }
+}
+
+// generalized chinese remainder theorem - moduli do not need to be coprime
+pub fn chinese_remainder( congruences: &[ Congruence ] ) -> Result< Congruence, CongruenceError >
+{
+    let mut result = Congruence::new( 0, 1 );
+
+    for congruence in congruences
+    {
+        result = result.combine( congruence )?;
+    }
+
+    Ok( result )
+}
+
+// Smallest t such that for every ( offset, period ) pair t = offset + k * period for some k >= 0.
+// A period of 0 means that the event happens only once, exactly at offset.
+pub fn earliest_common_time( events: &[ ( u64, u64 ) ] ) -> Result< u128, CongruenceError >
+{
+    if events.is_empty()
+    {
+        return Err( CongruenceError::NoSolution );
+    }
+
+    let happens_at = | t: u128, ( offset, period ): ( u64, u64 ) | -> bool
+    {
+        let ( offset, period ) = ( offset as u128, period as u128 );
+
+        if period == 0 { t == offset } else { t >= offset && ( t - offset ).is_multiple_of( period ) }
+    };
+
+    // single occurrence fixes the only candidate
+    if let Some( ( offset, _ ) ) = events.iter().find( | ( _, period ) | *period == 0 )
+    {
+        let t = *offset as u128;
+
+        return events.iter().all( | event | happens_at( t, *event ) ).then_some( t ).ok_or( CongruenceError::NoSolution );
+    }
+
+    let congruences: Vec< _ > = events.iter().map
+    (
+        | ( offset, period ) | Congruence::new( *offset as i128, *period as i128 )
+    ).collect();
+
+    let combined = chinese_remainder( &congruences )?;
+
+    let min_time = events.iter().map( | ( offset, _ ) | *offset as u128 ).max().unwrap();
+    let residue  = combined.residue as u128;
+    let modulus  = combined.modulus as u128;
+
+    if residue >= min_time
+    {
+        return Ok( residue );
+    }
+
+    let num_periods = ( min_time - residue ).div_ceil( modulus );
+
+    num_periods.checked_mul( modulus ).and_then( | step | residue.checked_add( step ) ).ok_or( CongruenceError::Overflow )
+}
+
+// All x in 0 ..= total for which x * ( total - x ) > threshold, solved exactly in integers.
+// The product is a downward parabola peaking at total / 2, so the solutions always form one contiguous range.
+pub fn range_where_product_exceeds( total: u64, threshold: u64 ) -> Option< RangeInclusive< u64 > >
+{
+    let total     = total as u128;
+    let threshold = threshold as u128;
+
+    let exceeds = | x: u128 | x * ( total - x ) > threshold;
+
+    let peak = total / 2;
+
+    if !exceeds( peak )
+    {
+        return None;
+    }
+
+    // x^2 - total * x + threshold < 0, roots are ( total -+ sqrt( total^2 - 4 * threshold ) ) / 2
+    // discriminant is positive since the peak exceeds threshold
+    let root = ( total * total - 4 * threshold ).isqrt();
+
+    // integer estimate is at most one off from the real boundary, walk it into place
+    let mut lower = ( total - root.min( total ) ) / 2;
+    while lower < peak && !exceeds( lower ) { lower += 1; }
+    while lower > 0    && exceeds( lower - 1 ) { lower -= 1; }
+
+    // parabola is symmetric around total / 2
+    let upper = total - lower;
+
+    Some( lower as u64 ..= upper as u64 )
+}
+
+#[test]
+fn test_gcd_lcm_multiple()
+{
+    assert_eq!( gcd_multiple( &[ 12, 18, 30 ] ), 6 );
+    assert_eq!( gcd_multiple( &[] ), 0 );
+    assert_eq!( lcm_multiple( &[ 4, 6, 10 ] ), 60 );
+    assert_eq!( lcm_multiple( &[] ), 1 );
+
+    assert_eq!( checked_lcm_multiple( &[ 4, 6, 10 ] ), Some( 60 ) );
+    assert_eq!( checked_lcm_multiple( &[ usize::MAX, usize::MAX - 1 ] ), Some( usize::MAX as u128 * ( usize::MAX - 1 ) as u128 ) );
+    assert_eq!( checked_lcm_multiple( &[ usize::MAX, usize::MAX - 1, usize::MAX - 2 ] ), None );
+}
+
+#[test]
+fn test_extended_gcd_and_inverse()
+{
+    for ( a, b ) in [ ( 240, 46 ), ( -7, 3 ), ( 0, 5 ), ( 17, 0 ), ( 12, -18 ) ]
+    {
+        let ( g, x, y ) = extended_gcd( a, b );
+        assert_eq!( g, gcd( a, b ) );
+        assert_eq!( a * x + b * y, g );
+    }
+
+    assert_eq!( mod_inverse( 3, 11 ), Some( 4 ) );
+    assert_eq!( mod_inverse( -3, 11 ), Some( 7 ) );
+    assert_eq!( mod_inverse( 6, 9 ), None );
+}
+
+#[test]
+fn test_chinese_remainder()
+{
+    let coprime = [ Congruence::new( 2, 3 ), Congruence::new( 3, 5 ), Congruence::new( 2, 7 ) ];
+    assert_eq!( chinese_remainder( &coprime ), Ok( Congruence::new( 23, 105 ) ) );
+
+    let non_coprime = [ Congruence::new( 3, 4 ), Congruence::new( 1, 6 ) ];
+    assert_eq!( chinese_remainder( &non_coprime ), Ok( Congruence::new( 7, 12 ) ) );
+
+    let inconsistent = [ Congruence::new( 1, 4 ), Congruence::new( 2, 6 ) ];
+    assert_eq!( chinese_remainder( &inconsistent ), Err( CongruenceError::NoSolution ) );
+
+    // k does not fit into i128 before it is reduced, the combined modulus does not fit at all
+    assert_eq!( Congruence::new( 1 << 99, ( 1 << 100 ) + 1 ).combine( &Congruence::new( 3, ( 1 << 100 ) - 1 ) ), Err( CongruenceError::Overflow ) );
+
+    // the combined modulus fits, even though k times the inverse does not
+    let large = Congruence::new( 2, 3 ).combine( &Congruence::new( 1 << 99, ( 1 << 100 ) + 1 ) ).unwrap();
+    assert_eq!( ( large.residue % 3, large.residue % ( ( 1 << 100 ) + 1 ) ), ( 2, 1 << 99 ) );
+    assert_eq!( large.modulus, 3 * ( ( 1 << 100 ) + 1 ) );
+}
+
+#[test]
+fn test_earliest_common_time()
+{
+    // plain lcm case
+    assert_eq!( earliest_common_time( &[ ( 2, 2 ), ( 3, 3 ) ] ), Ok( 6 ) );
+    // offsets larger than periods must not produce an earlier time
+    assert_eq!( earliest_common_time( &[ ( 14, 4 ), ( 1, 3 ) ] ), Ok( 22 ) );
+    assert_eq!( earliest_common_time( &[ ( 1, 4 ), ( 2, 6 ) ] ), Err( CongruenceError::NoSolution ) );
+    assert_eq!( earliest_common_time( &[ ( 9, 0 ), ( 1, 4 ) ] ), Ok( 9 ) );
+    assert_eq!( earliest_common_time( &[ ( 8, 0 ), ( 1, 4 ) ] ), Err( CongruenceError::NoSolution ) );
+
+    // a solution exists below 2^128, but the combined modulus does not fit into i128
+    assert_eq!( earliest_common_time( &[ ( 1, u64::MAX ), ( 0, u64::MAX - 1 ) ] ), Err( CongruenceError::Overflow ) );
+
+    // brute force cross check
+    for a in 0 .. 8u64
+    {
+        for p in 1 .. 6u64
+        {
+            for b in 0 .. 8u64
+            {
+                for q in 1 .. 6u64
+                {
+                    let expected = ( 0 .. 200u128 ).find
+                    (
+                        | t |
+                        {
+                            *t >= a as u128 && ( t - a as u128 ).is_multiple_of( p as u128 ) &&
+                            *t >= b as u128 && ( t - b as u128 ).is_multiple_of( q as u128 )
+                        }
+                    );
+                    assert_eq!( earliest_common_time( &[ ( a, p ), ( b, q ) ] ).ok(), expected );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_range_where_product_exceeds_brute_force()
+{
+    for total in 0 .. 60u64
+    {
+        for threshold in 0 .. 1000u64
+        {
+            let winning: Vec< u64 > = ( 0 ..= total ).filter( | x | x * ( total - x ) > threshold ).collect();
+
+            let expected = if winning.is_empty() { None } else { Some( winning[ 0 ] ..= *winning.last().unwrap() ) };
+
+            assert_eq!( range_where_product_exceeds( total, threshold ), expected, "total={}, threshold={}", total, threshold );
+        }
+    }
+}
+
+#[test]
+fn test_range_where_product_exceeds_large()
+{
+    // beyond 2^53, where f64 square roots stop being exact
+    let cases =
+    [
+        ( 1u64 << 40, 1u64 << 60 ),
+        ( ( 1 << 33 ) + 7, ( 1 << 63 ) + 12345 ),
+        ( 3_000_000_000, 2_249_999_999_999_999_999 ),
+    ];
+
+    for ( total, threshold ) in cases
+    {
+        let range = range_where_product_exceeds( total, threshold ).unwrap();
+
+        let product = | x: u64 | x as u128 * ( total - x ) as u128;
+
+        assert!( product( *range.start() ) > threshold as u128 );
+        assert!( product( *range.end()   ) > threshold as u128 );
+        assert!( *range.start() == 0     || product( range.start() - 1 ) <= threshold as u128 );
+        assert!( *range.end()   == total || product( range.end()   + 1 ) <= threshold as u128 );
+    }
+
+    assert_eq!( range_where_product_exceeds( 3_000_000_000, 2_250_000_000_000_000_000 ), None );
+    assert_eq!( range_where_product_exceeds( 10, 25 ), None );
+    assert_eq!( range_where_product_exceeds( 10, 24 ), Some( 5 ..= 5 ) );
+}
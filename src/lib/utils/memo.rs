@@ -0,0 +1,297 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+pub trait Cache< K, V >
+{
+    fn get( &self, key: &K ) -> Option< &V >;
+    fn insert( &mut self, key: K, value: V );
+}
+
+impl< K, V > Cache< K, V > for HashMap< K, V > where K: Hash + Eq
+{
+    fn get( &self, key: &K ) -> Option< &V >
+    {
+        HashMap::get( self, key )
+    }
+
+    fn insert( &mut self, key: K, value: V )
+    {
+        HashMap::insert( self, key, value );
+    }
+}
+
+// hash map cache that holds at most `capacity` entries, evicting the oldest inserted ones first
+pub struct BoundedCache< K, V >
+{
+    map     : HashMap< K, V >,
+    order   : VecDeque< K >,
+    capacity: usize,
+}
+
+impl< K, V > BoundedCache< K, V > where K: Hash + Eq + Clone
+{
+    pub fn new( capacity: usize ) -> BoundedCache< K, V >
+    {
+        BoundedCache { map: HashMap::new(), order: VecDeque::new(), capacity }
+    }
+
+    pub fn len( &self ) -> usize
+    {
+        self.map.len()
+    }
+
+    pub fn is_empty( &self ) -> bool
+    {
+        self.map.is_empty()
+    }
+}
+
+impl< K, V > Cache< K, V > for BoundedCache< K, V > where K: Hash + Eq + Clone
+{
+    fn get( &self, key: &K ) -> Option< &V >
+    {
+        self.map.get( key )
+    }
+
+    fn insert( &mut self, key: K, value: V )
+    {
+        if self.capacity == 0
+        {
+            return;
+        }
+
+        if let Some( existing ) = self.map.get_mut( &key )
+        {
+            *existing = value;
+            return;
+        }
+
+        while self.map.len() >= self.capacity
+        {
+            let oldest = self.order.pop_front().unwrap();
+            self.map.remove( &oldest );
+        }
+
+        self.order.push_back( key.clone() );
+        self.map.insert( key, value );
+    }
+}
+
+// Keys that are small tuples of integers and can be laid out in a dense N-dimensional array.
+pub trait DenseKey< const N: usize >
+{
+    fn coordinates( &self ) -> [ usize; N ];
+}
+
+impl< const N: usize > DenseKey< N > for [ usize; N ]
+{
+    fn coordinates( &self ) -> [ usize; N ]
+    {
+        *self
+    }
+}
+
+impl DenseKey< 2 > for ( usize, usize )
+{
+    fn coordinates( &self ) -> [ usize; 2 ]
+    {
+        [ self.0, self.1 ]
+    }
+}
+
+impl DenseKey< 3 > for ( usize, usize, usize )
+{
+    fn coordinates( &self ) -> [ usize; 3 ]
+    {
+        [ self.0, self.1, self.2 ]
+    }
+}
+
+// Array backed cache for keys with every coordinate in 0 .. dims[ i ].
+// Keys outside of the dimensions are simply never cached.
+pub struct DenseCache< V, const N: usize >
+{
+    data: Vec< Option< V > >,
+    dims: [ usize; N ],
+}
+
+impl< V, const N: usize > DenseCache< V, N > where V: Clone
+{
+    pub fn new( dims: [ usize; N ] ) -> DenseCache< V, N >
+    {
+        DenseCache { data: vec![ None; dims.iter().product() ], dims }
+    }
+
+    fn index( &self, coordinates: [ usize; N ] ) -> Option< usize >
+    {
+        let mut index = 0usize;
+
+        for ( coordinate, dim ) in coordinates.iter().zip( self.dims )
+        {
+            if *coordinate >= dim
+            {
+                return None;
+            }
+            index = index * dim + coordinate;
+        }
+
+        Some( index )
+    }
+}
+
+impl< K, V, const N: usize > Cache< K, V > for DenseCache< V, N > where K: DenseKey< N >, V: Clone
+{
+    fn get( &self, key: &K ) -> Option< &V >
+    {
+        let index = self.index( key.coordinates() )?;
+        self.data[ index ].as_ref()
+    }
+
+    fn insert( &mut self, key: K, value: V )
+    {
+        if let Some( index ) = self.index( key.coordinates() )
+        {
+            self.data[ index ] = Some( value );
+        }
+    }
+}
+
+#[derive( Debug, Default, Clone, Copy, PartialEq, Eq )]
+pub struct CacheStats
+{
+    pub hits  : usize,
+    pub misses: usize,
+}
+
+impl CacheStats
+{
+    pub fn hit_rate( &self ) -> f64
+    {
+        let total = self.hits + self.misses;
+
+        if total == 0 { 0f64 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+// Memoizes a recursive function. The function receives a callback for the recursive calls,
+// which go through the cache as well:
+//
+//     memo.solve( n, &| fib, n | if *n < 2 { *n } else { fib( n - 1 ) + fib( n - 2 ) } )
+pub struct Memo< K, V, C = HashMap< K, V > >
+{
+    cache: C,
+    stats: CacheStats,
+    _marker: PhantomData< ( K, V ) >,
+}
+
+impl< K, V > Memo< K, V > where K: Hash + Eq, V: Clone
+{
+    pub fn new() -> Memo< K, V >
+    {
+        Memo::with_cache( HashMap::new() )
+    }
+}
+
+impl< K, V > Default for Memo< K, V > where K: Hash + Eq, V: Clone
+{
+    fn default() -> Self
+    {
+        Memo::new()
+    }
+}
+
+impl< K, V, C > Memo< K, V, C > where C: Cache< K, V >, V: Clone
+{
+    pub fn with_cache( cache: C ) -> Memo< K, V, C >
+    {
+        Memo { cache, stats: CacheStats::default(), _marker: PhantomData }
+    }
+
+    pub fn solve< F >( &mut self, key: K, function: &F ) -> V
+    where
+        F: Fn( &mut dyn FnMut( K ) -> V, &K ) -> V,
+    {
+        if let Some( value ) = self.cache.get( &key )
+        {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+
+        self.stats.misses += 1;
+
+        let value = function( &mut | next | self.solve( next, function ), &key );
+
+        self.cache.insert( key, value.clone() );
+
+        value
+    }
+
+    pub fn stats( &self ) -> CacheStats
+    {
+        self.stats
+    }
+
+    pub fn cache( &self ) -> &C
+    {
+        &self.cache
+    }
+}
+
+#[cfg(test)]
+fn fibonacci< C >( memo: &mut Memo< usize, u64, C >, n: usize ) -> u64 where C: Cache< usize, u64 >
+{
+    memo.solve( n, &| fib, n | if *n < 2 { *n as u64 } else { fib( n - 1 ) + fib( n - 2 ) } )
+}
+
+#[test]
+fn test_memo_hash_cache()
+{
+    let mut memo = Memo::new();
+
+    assert_eq!( fibonacci( &mut memo, 90 ), 2880067194370816120 );
+    assert_eq!( memo.stats(), CacheStats { hits: 88, misses: 91 } );
+
+    // everything is cached now
+    assert_eq!( fibonacci( &mut memo, 90 ), 2880067194370816120 );
+    assert_eq!( memo.stats().hits, 89 );
+}
+
+#[test]
+fn test_memo_bounded_cache()
+{
+    let mut memo = Memo::with_cache( BoundedCache::new( 4 ) );
+
+    assert_eq!( fibonacci( &mut memo, 50 ), 12586269025 );
+    assert_eq!( memo.cache().len(), 4 );
+
+    // only the most recent entries are kept, so an early value has to be recomputed
+    let misses = memo.stats().misses;
+    fibonacci( &mut memo, 3 );
+    assert!( memo.stats().misses > misses );
+}
+
+#[test]
+fn test_memo_dense_cache()
+{
+    // number of monotonic lattice paths, keyed by ( row, col )
+    let mut memo = Memo::with_cache( DenseCache::new( [ 17, 17 ] ) );
+
+    let paths = memo.solve
+    (
+        ( 16, 16 ),
+        &| paths, ( row, col ): &( usize, usize ) |
+        {
+            if *row == 0 || *col == 0 { 1u64 } else { paths( ( row - 1, *col ) ) + paths( ( *row, col - 1 ) ) }
+        }
+    );
+
+    assert_eq!( paths, 601080390 );
+    assert_eq!( memo.stats().misses, 17 * 17 - 1 );
+
+    // keys outside of the dimensions still work, they just never hit the cache
+    let mut memo: Memo< ( usize, usize ), u64, _ > = Memo::with_cache( DenseCache::new( [ 1, 1 ] ) );
+    assert_eq!( memo.solve( ( 3, 0 ), &| _, ( row, _ ) | *row as u64 ), 3 );
+    assert_eq!( memo.solve( ( 3, 0 ), &| _, ( row, _ ) | *row as u64 ), 3 );
+    assert_eq!( memo.stats(), CacheStats { hits: 0, misses: 2 } );
+}
@@ -1 +1,6 @@
+pub mod input;
+pub mod matcher;
+pub mod math;
 pub mod matrix;
+pub mod memo;
+pub mod sequence;
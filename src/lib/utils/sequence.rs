@@ -0,0 +1,220 @@
+use num_integer::gcd;
+use num_rational::Ratio;
+
+#[derive( Debug, PartialEq, Eq )]
+pub enum SequenceError
+{
+    // difference table did not reach a constant row up to ( and including ) this degree
+    NotPolynomial { checked_degree: usize },
+    Overflow,
+}
+
+// Minimal polynomial through a sequence, kept in Newton forward form: value at index n is
+// sum of leading_differences[ j ] * binomial( n, j ), where index 0 is the first sample.
+#[derive( Debug, Clone )]
+pub struct PolynomialFit
+{
+    leading_differences: Vec< i128 >,
+    num_samples        : usize,
+}
+
+fn differences( row: &[ i128 ] ) -> Result< Vec< i128 >, SequenceError >
+{
+    row.windows( 2 ).map( | x | x[ 1 ].checked_sub( x[ 0 ] ).ok_or( SequenceError::Overflow ) ).collect()
+}
+
+// generalized binomial coefficient, also valid for negative n
+fn binomial( n: i128, k: usize ) -> Option< i128 >
+{
+    let mut result = 1i128;
+
+    for i in 0 .. k as i128
+    {
+        // product of i + 1 consecutive integers is always divisible by ( i + 1 )!, so this stays exact
+        result = result.checked_mul( n - i )? / ( i + 1 );
+    }
+
+    Some( result )
+}
+
+pub fn fit_polynomial( values: &[ i64 ], max_degree: Option< usize > ) -> Result< PolynomialFit, SequenceError >
+{
+    let mut leading_differences: Vec< i128 > = Vec::new();
+
+    let mut row: Vec< i128 > = values.iter().map( | x | *x as i128 ).collect();
+
+    loop
+    {
+        let degree = leading_differences.len();
+
+        // a constant row is only confirmed if there are at least two samples in it
+        if row.len() < 2
+        {
+            return Err( SequenceError::NotPolynomial { checked_degree: degree.saturating_sub( 1 ) } );
+        }
+
+        leading_differences.push( row[ 0 ] );
+
+        if row.iter().all( | x | *x == row[ 0 ] )
+        {
+            return Ok( PolynomialFit { leading_differences, num_samples: values.len() } );
+        }
+
+        if Some( degree ) == max_degree
+        {
+            return Err( SequenceError::NotPolynomial { checked_degree: degree } );
+        }
+
+        row = differences( &row )?;
+    }
+}
+
+impl PolynomialFit
+{
+    pub fn degree( &self ) -> usize
+    {
+        self.leading_differences.len() - 1
+    }
+
+    pub fn value_at( &self, index: i64 ) -> Result< i128, SequenceError >
+    {
+        let mut result = 0i128;
+
+        for ( k, difference ) in self.leading_differences.iter().enumerate()
+        {
+            let term = binomial( index as i128, k ).and_then( | b | b.checked_mul( *difference ) );
+
+            result = term.and_then( | t | result.checked_add( t ) ).ok_or( SequenceError::Overflow )?;
+        }
+
+        Ok( result )
+    }
+
+    // next k values after the last sample
+    pub fn extrapolate_forward( &self, k: usize ) -> Result< Vec< i128 >, SequenceError >
+    {
+        let first = self.num_samples as i64;
+
+        ( first .. first + k as i64 ).map( | index | self.value_at( index ) ).collect()
+    }
+
+    // k values before the first sample, nearest one first
+    pub fn extrapolate_backward( &self, k: usize ) -> Result< Vec< i128 >, SequenceError >
+    {
+        ( 1 ..= k as i64 ).map( | offset | self.value_at( -offset ) ).collect()
+    }
+
+    // coefficients c[ i ] of sum c[ i ] * n^i, where n is the index of the sample
+    pub fn coefficients( &self ) -> Result< Vec< Ratio< i128 > >, SequenceError >
+    {
+        let mut coefficients = vec![ Ratio::from_integer( 0i128 ); self.leading_differences.len() ];
+
+        // coefficients of the falling factorial n * ( n - 1 ) * ... * ( n - k + 1 )
+        let mut falling: Vec< i128 > = vec![ 1 ];
+        let mut factorial = 1i128;
+
+        for ( k, difference ) in self.leading_differences.iter().enumerate()
+        {
+            if k > 0
+            {
+                // multiply by ( n - ( k - 1 ) )
+                let shift = ( k - 1 ) as i128;
+                let mut next = vec![ 0i128; falling.len() + 1 ];
+                for ( power, coefficient ) in falling.iter().enumerate()
+                {
+                    next[ power + 1 ] = next[ power + 1 ].checked_add( *coefficient ).ok_or( SequenceError::Overflow )?;
+                    next[ power ]     = coefficient.checked_mul( shift )
+                        .and_then( | product | next[ power ].checked_sub( product ) )
+                        .ok_or( SequenceError::Overflow )?;
+                }
+                falling = next;
+                factorial = factorial.checked_mul( k as i128 ).ok_or( SequenceError::Overflow )?;
+            }
+
+            for ( power, coefficient ) in falling.iter().enumerate()
+            {
+                let term = difference.checked_mul( *coefficient ).ok_or( SequenceError::Overflow )?;
+
+                coefficients[ power ] = checked_add_ratio( &coefficients[ power ], &Ratio::new( term, factorial ) )?;
+            }
+        }
+
+        Ok( coefficients )
+    }
+}
+
+// a / b + c / d over the common denominator lcm( b, d ), reduced afterwards
+fn checked_add_ratio( x: &Ratio< i128 >, y: &Ratio< i128 > ) -> Result< Ratio< i128 >, SequenceError >
+{
+    let divisor = gcd( *x.denom(), *y.denom() );
+    let x_scale = y.denom() / divisor;
+    let y_scale = x.denom() / divisor;
+
+    let numer = x.numer().checked_mul( x_scale )
+        .zip( y.numer().checked_mul( y_scale ) )
+        .and_then( | ( a, b ) | a.checked_add( b ) )
+        .ok_or( SequenceError::Overflow )?;
+    let denom = x.denom().checked_mul( x_scale ).ok_or( SequenceError::Overflow )?;
+
+    Ok( Ratio::new( numer, denom ) )
+}
+
+#[test]
+fn test_fit_and_extrapolate()
+{
+    let fit = fit_polynomial( &[ 10, 13, 16, 21, 30, 45 ], None ).unwrap();
+
+    assert_eq!( fit.degree(), 3 );
+    assert_eq!( fit.extrapolate_forward( 2 ), Ok( vec![ 68, 101 ] ) );
+    assert_eq!( fit.extrapolate_backward( 2 ), Ok( vec![ 5, -4 ] ) );
+
+    let constant = fit_polynomial( &[ 0, 0, 0 ], None ).unwrap();
+    assert_eq!( constant.degree(), 0 );
+    assert_eq!( constant.extrapolate_backward( 1 ), Ok( vec![ 0 ] ) );
+}
+
+#[test]
+fn test_coefficients()
+{
+    // n^2 / 2 + n / 2 + 1
+    let fit = fit_polynomial( &[ 1, 2, 4, 7, 11 ], None ).unwrap();
+
+    assert_eq!( fit.coefficients(), Ok( vec![ Ratio::from_integer( 1 ), Ratio::new( 1, 2 ), Ratio::new( 1, 2 ) ] ) );
+
+    // 2n^3 - 3n + 5
+    let values: Vec< i64 > = ( 0 .. 6 ).map( | n: i64 | 2 * n * n * n - 3 * n + 5 ).collect();
+    let fit = fit_polynomial( &values, None ).unwrap();
+
+    assert_eq!( fit.coefficients(), Ok( [ 5, -3, 0, 2 ].map( Ratio::from_integer ).to_vec() ) );
+}
+
+#[test]
+fn test_not_polynomial()
+{
+    let not_polynomial = | checked_degree | SequenceError::NotPolynomial { checked_degree };
+
+    assert_eq!( fit_polynomial( &[ 1, 2, 4, 8, 16, 32 ], None ).unwrap_err(), not_polynomial( 4 ) );
+    assert_eq!( fit_polynomial( &[ 1, 4, 9, 16 ], Some( 1 ) ).unwrap_err(), not_polynomial( 1 ) );
+    assert_eq!( fit_polynomial( &[ 7 ], None ).unwrap_err(), not_polynomial( 0 ) );
+    assert_eq!( fit_polynomial( &[], None ).unwrap_err(), not_polynomial( 0 ) );
+
+    assert!( fit_polynomial( &[ 1, 4, 9, 16 ], Some( 2 ) ).is_ok() );
+}
+
+#[test]
+fn test_overflow()
+{
+    // 2^60 * n * ( n - 1 ) / 2
+    let fit = fit_polynomial( &[ 0, 0, 1 << 60, 3 << 60 ], None ).unwrap();
+
+    assert_eq!( fit.degree(), 2 );
+    assert!( fit.value_at( 1 << 30 ).is_ok() );
+    assert_eq!( fit.value_at( 1 << 40 ).unwrap_err(), SequenceError::Overflow );
+
+    // binomial( n, 35 ) extrapolates fine, but 35! and the falling factorial coefficients do not fit
+    let samples: Vec< i64 > = ( 0 .. 37 ).map( | n | match n { 35 => 1, 36 => 36, _ => 0 } ).collect();
+    let fit = fit_polynomial( &samples, None ).unwrap();
+
+    assert_eq!( fit.extrapolate_forward( 1 ), Ok( vec![ 666 ] ) );
+    assert_eq!( fit.coefficients(), Err( SequenceError::Overflow ) );
+}